@@ -3,63 +3,241 @@ use std::{
     collections::{HashMap, HashSet},
 };
 
-use levenshtein::levenshtein;
+use anyhow::Context;
+use serde::{Deserialize, Serialize};
 
 use tracing::{instrument, trace};
 
 use crate::{
+    explain,
+    names::{NameMatcher, NameMatching},
     query::*,
+    search::resolve_type,
     types::{self, Generics, Item},
     Crate,
 };
 
-#[derive(Debug, Clone, PartialEq)]
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
 pub enum Similarity {
     /// Represents how digitally similar two objects are, with a brief reason.
     Discrete {
         kind: DiscreteSimilarity,
-        reason: String,
+        reason: SimilarityReason,
+        category: Category,
     },
 
     /// Represents how analogly similar two objects are, with a brief reason.
-    Continuous { value: f32, reason: String },
+    Continuous {
+        value: f32,
+        reason: SimilarityReason,
+        category: Category,
+    },
 }
 
 impl Similarity {
     pub fn score(&self) -> f32 {
+        self.score_with(&ScoringProfile::default())
+    }
+
+    /// Score of this similarity, with the subequal score taken from `profile`.
+    pub fn score_with(&self, profile: &ScoringProfile) -> f32 {
         match self {
             Discrete {
                 kind: Equivalent, ..
             } => 0.0,
-            Discrete { kind: Subequal, .. } => 0.25,
+            Discrete { kind: Subequal, .. } => profile.subequal,
             Discrete {
                 kind: Different, ..
             } => 1.0,
             Continuous { value, .. } => *value,
         }
     }
+
+    pub fn reason(&self) -> &SimilarityReason {
+        match self {
+            Discrete { reason, .. } | Continuous { reason, .. } => reason,
+        }
+    }
+
+    pub fn category(&self) -> Category {
+        match self {
+            Discrete { category, .. } | Continuous { category, .. } => *category,
+        }
+    }
+
+    fn set_category(&mut self, to: Category) {
+        match self {
+            Discrete { category, .. } | Continuous { category, .. } => *category = to,
+        }
+    }
 }
 
 use Similarity::*;
 
-#[derive(Debug, Clone, PartialEq, Default)]
+/// The part of a signature a similarity was found in, used to weight it when scoring.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum Category {
+    /// Item and symbol names.
+    Name,
+    /// Types of function arguments, including the receiver.
+    Argument,
+    /// Return types.
+    Return,
+    /// Path qualifiers, e.g. `io::` in `io::Error`.
+    Qualifier,
+    /// Generic parameters, holes, const generics and trait bounds.
+    Generic,
+    /// References, pointers, slices, arrays, trait objects and coercions around a type.
+    Wrapper,
+    /// Number of arguments, tuple fields or generic args.
+    Arity,
+    /// How loosely typed the candidate signature is, see [`looseness`].
+    Specificity,
+    /// Anything else, e.g. item kinds and primitive types.
+    Other,
+}
+
+/// Weights for each [`Category`] of similarity, used to rank search hits, along with how names
+/// are matched.
+///
+/// The default profile weights every category equally, and can be loaded from a TOML or JSON
+/// file where missing fields keep their default:
+///
+/// ```toml
+/// subequal = 0.5
+/// name = 0.1
+/// return = 2.0
+///
+/// [names]
+/// strategy = "levenshtein"
+/// ```
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct ScoringProfile {
+    /// Score of a subequal similarity, between equivalent (0) and different (1).
+    pub subequal: f32,
+    pub name: f32,
+    pub argument: f32,
+    #[serde(rename = "return")]
+    pub return_: f32,
+    pub qualifier: f32,
+    pub generic: f32,
+    pub wrapper: f32,
+    pub arity: f32,
+    pub specificity: f32,
+    pub other: f32,
+    /// How query names are matched against item names.
+    pub names: NameMatching,
+}
+
+impl Default for ScoringProfile {
+    fn default() -> Self {
+        Self {
+            subequal: 0.25,
+            name: 1.0,
+            argument: 1.0,
+            return_: 1.0,
+            qualifier: 1.0,
+            generic: 1.0,
+            wrapper: 1.0,
+            arity: 1.0,
+            specificity: 0.25,
+            other: 1.0,
+            names: NameMatching::default(),
+        }
+    }
+}
+
+impl ScoringProfile {
+    pub fn weight(&self, category: Category) -> f32 {
+        match category {
+            Category::Name => self.name,
+            Category::Argument => self.argument,
+            Category::Return => self.return_,
+            Category::Qualifier => self.qualifier,
+            Category::Generic => self.generic,
+            Category::Wrapper => self.wrapper,
+            Category::Arity => self.arity,
+            Category::Specificity => self.specificity,
+            Category::Other => self.other,
+        }
+    }
+
+    /// Load a profile from a TOML file, or a JSON file when `path` ends with `.json`.
+    pub fn load(path: impl AsRef<std::path::Path>) -> anyhow::Result<Self> {
+        let path = path.as_ref();
+        let content = std::fs::read_to_string(path)
+            .with_context(|| format!("reading scoring profile {}", path.display()))?;
+        let profile = if path.extension().is_some_and(|ext| ext == "json") {
+            serde_json::from_str(&content)
+                .with_context(|| format!("parsing scoring profile {}", path.display()))?
+        } else {
+            toml::from_str(&content)
+                .with_context(|| format!("parsing scoring profile {}", path.display()))?
+        };
+        Self::validate(&profile)
+            .with_context(|| format!("invalid scoring profile {}", path.display()))?;
+        Ok(profile)
+    }
+
+    /// Check that weights are non-negative and not all zero, and that `subequal` is between
+    /// equivalent and different, so that scores stay defined and ordered.
+    pub fn validate(&self) -> anyhow::Result<()> {
+        let weights = [
+            ("name", self.name),
+            ("argument", self.argument),
+            ("return", self.return_),
+            ("qualifier", self.qualifier),
+            ("generic", self.generic),
+            ("wrapper", self.wrapper),
+            ("arity", self.arity),
+            ("specificity", self.specificity),
+            ("other", self.other),
+        ];
+        if let Some((name, weight)) = weights
+            .iter()
+            .find(|(_, weight)| !(weight.is_finite() && *weight >= 0.0))
+        {
+            anyhow::bail!("weight `{}` must be non-negative, got {}", name, weight);
+        }
+        if weights.iter().all(|(_, weight)| *weight == 0.0) {
+            anyhow::bail!("at least one weight must be positive");
+        }
+        if !(0.0..=1.0).contains(&self.subequal) {
+            anyhow::bail!("`subequal` must be between 0 and 1, got {}", self.subequal);
+        }
+        Ok(())
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Default, Serialize, Deserialize)]
 pub struct Similarities(pub Vec<Similarity>);
 
 impl Similarities {
-    /// Calculate objective similarity for sorting.
-    pub fn score(&self) -> f32 {
-        let sum: f32 = self.0.iter().map(|sim| sim.score()).sum();
-        sum / self.0.len() as f32
+    pub fn is_empty(&self) -> bool {
+        self.0.is_empty()
     }
-}
 
-impl PartialOrd for Similarities {
-    fn partial_cmp(&self, other: &Self) -> Option<std::cmp::Ordering> {
-        (self.score()).partial_cmp(&other.score())
+    /// Weighted mean of the similarities, weighting each by its category in `profile`.
+    ///
+    /// Similarities that all weigh nothing, or no similarities at all, score 0.
+    pub fn score_with(&self, profile: &ScoringProfile) -> f32 {
+        let (sum, weights) = self.0.iter().fold((0.0, 0.0), |(sum, weights), sim| {
+            let weight = profile.weight(sim.category());
+            (sum + weight * sim.score_with(profile), weights + weight)
+        });
+        if weights > 0.0 {
+            sum / weights
+        } else {
+            0.0
+        }
     }
 }
 
-#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
 pub enum DiscreteSimilarity {
     /// Indicates that two types are the same.
     ///
@@ -84,71 +262,381 @@ pub enum DiscreteSimilarity {
 
 use DiscreteSimilarity::*;
 
+/// Why a [`Similarity`] was found, displayed as a brief explanation.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum SimilarityReason {
+    // Names and paths.
+    MissingItemName,
+    SymbolNameDistance,
+    QualifiedPathUnknown,
+    QualifiedPathMatches,
+    QualifiedPathDiffers,
+    /// The query is for another kind of item.
+    QueryExpects(ItemKind),
+
+    // Macros and type definitions.
+    MacroKindMatches,
+    MacroKindDiffers,
+    NoMacroMatcher,
+    ProcMacroHasNoMatcher,
+    FragmentSpecifierMatches,
+    MissingFragmentSpecifier,
+    ExtraFragmentSpecifier,
+    NoFragmentSpecifiers,
+    GenericArityMatches,
+    GenericArityDiffers,
+
+    // Function signatures.
+    QualifierMatches,
+    QualifierDiffers,
+    MissingQualifier(Qualifier),
+    ExtraQualifier(Qualifier),
+    LooselyTypedSignature,
+    MissingReceiver,
+    ReceiverModeMatches,
+    ReceiverModeDiffers {
+        query: ReceiverMode,
+        candidate: ReceiverMode,
+    },
+    /// Query arguments in the order of the candidate arguments they were assigned to, `None`
+    /// for unassigned ones.
+    ArgumentsReordered(Vec<Option<usize>>),
+    /// The query argument at `index` has no candidate argument.
+    MissingArgument {
+        index: usize,
+    },
+    /// The candidate argument at `index` has no query argument.
+    ExtraArgument {
+        index: usize,
+    },
+    NoArguments,
+    UnitReturn,
+    ReturnTypeDiffers,
+
+    // Generics, holes and bounds.
+    HoleMatchesBinding,
+    HoleBoundInconsistently,
+    HoleBound,
+    HoleMatchesUnrepresentable,
+    SelfMatches,
+    UnboundSelf,
+    GenericMatchesSubstitution,
+    GenericDiffersFromSubstitution,
+    GenericSubstituted,
+    /// The query type substituting a generic doesn't implement one of its bounds.
+    UnmetBound {
+        type_: Type,
+        trait_: String,
+    },
+    UnsupportedTraitBound,
+    TraitBoundMatches,
+    MissingTraitBound,
+    ConstGenericSubstituted,
+    ConstMatches,
+    ConstDiffers,
+    MissingGenericArg,
+    MissingGenericArgs,
+    GenericArgsShapeDiffers,
+    AssocConstraintDiffers,
+    MissingAssocConstraint,
+
+    // Types.
+    InherentAssociatedType,
+    UnresolvedProjection,
+    TupleShape,
+    TupleLengthDiffers,
+    ArrayType,
+    SliceType,
+    SliceAndArray,
+    MutabilityDiffers,
+    PointerWrapper,
+    OpaqueTypeAndTraitObject,
+    ClosureTraitMatches,
+    ClosureTraitDiffers,
+    FunctionPointer,
+    FunctionPointerAndClosure,
+    ArgumentCountDiffers {
+        query: usize,
+        candidate: usize,
+    },
+    DerefCoercion,
+    AsRefConversion,
+    BorrowConversion,
+    PrimitiveMatches,
+    PrimitiveDiffers,
+    TypeMismatch {
+        query: Box<Type>,
+        candidate: Box<types::Type>,
+    },
+}
+
+impl std::fmt::Display for SimilarityReason {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        use SimilarityReason::*;
+        match self {
+            MissingItemName => write!(f, "missing item name"),
+            SymbolNameDistance => write!(f, "symbol name distance"),
+            QualifiedPathUnknown => write!(f, "qualified path unknown"),
+            QualifiedPathMatches => write!(f, "qualified path matches"),
+            QualifiedPathDiffers => write!(f, "qualified path differs"),
+            QueryExpects(kind) => {
+                let kind = match kind {
+                    ItemKind::Function | ItemKind::Method => "function",
+                    ItemKind::Macro => "macro",
+                    ItemKind::Struct => "struct",
+                    ItemKind::Enum => "enum",
+                    ItemKind::Union => "union",
+                    ItemKind::Trait => "trait",
+                    ItemKind::TypeAlias => "type alias",
+                };
+                write!(f, "query expects {}", kind)
+            }
+            MacroKindMatches => write!(f, "macro kind matches"),
+            MacroKindDiffers => write!(f, "macro kind differs"),
+            NoMacroMatcher => write!(f, "no macro matcher"),
+            ProcMacroHasNoMatcher => write!(f, "proc macro has no matcher"),
+            FragmentSpecifierMatches => write!(f, "fragment specifier matches"),
+            MissingFragmentSpecifier => write!(f, "missing fragment specifier"),
+            ExtraFragmentSpecifier => write!(f, "extra fragment specifier"),
+            NoFragmentSpecifiers => write!(f, "no fragment specifiers"),
+            GenericArityMatches => write!(f, "generic arity matches"),
+            GenericArityDiffers => write!(f, "generic arity differs"),
+            QualifierMatches => write!(f, "qualifier matched"),
+            QualifierDiffers => write!(f, "qualifier different"),
+            MissingQualifier(qualifier) => write!(f, "missing qualifier `{}`", qualifier),
+            ExtraQualifier(qualifier) => write!(f, "extra qualifier `{}`", qualifier),
+            LooselyTypedSignature => write!(f, "loosely typed signature"),
+            MissingReceiver => write!(f, "missing receiver"),
+            ReceiverModeMatches => write!(f, "receiver mode matches"),
+            ReceiverModeDiffers { query, candidate } => {
+                write!(f, "receiver is `{}`, not `{}`", candidate, query)
+            }
+            ArgumentsReordered(order) => {
+                let order = order
+                    .iter()
+                    .map(|idx| idx.map_or("_".to_string(), |idx| idx.to_string()))
+                    .collect::<Vec<_>>();
+                write!(f, "arguments reordered to ({})", order.join(", "))
+            }
+            MissingArgument { index } => write!(f, "missing argument {}", index),
+            ExtraArgument { index } => write!(f, "extra argument {}", index),
+            NoArguments => write!(f, "no arguments"),
+            UnitReturn => write!(f, "unit return"),
+            ReturnTypeDiffers => write!(f, "return type differs"),
+            HoleMatchesBinding => write!(f, "hole matches its binding"),
+            HoleBoundInconsistently => write!(f, "hole bound inconsistently"),
+            HoleBound => write!(f, "hole bound"),
+            HoleMatchesUnrepresentable => write!(f, "hole matches an unrepresentable type"),
+            SelfMatches => write!(f, "Self matches"),
+            UnboundSelf => write!(f, "unbound Self in where-predicate"),
+            GenericMatchesSubstitution => write!(f, "generic matches substitution"),
+            GenericDiffersFromSubstitution => write!(f, "generic differs from substitution"),
+            GenericSubstituted => write!(f, "generic substituted"),
+            UnmetBound { type_, trait_ } => write!(f, "`{}` doesn't implement `{}`", type_, trait_),
+            UnsupportedTraitBound => write!(f, "unsupported trait bound"),
+            TraitBoundMatches => write!(f, "trait bound matches"),
+            MissingTraitBound => write!(f, "missing trait bound"),
+            ConstGenericSubstituted => write!(f, "const generic substituted"),
+            ConstMatches => write!(f, "const matches"),
+            ConstDiffers => write!(f, "const differs"),
+            MissingGenericArg => write!(f, "missing generic arg"),
+            MissingGenericArgs => write!(f, "missing generic args"),
+            GenericArgsShapeDiffers => write!(f, "generic args shape differs"),
+            AssocConstraintDiffers => write!(f, "associated item constrained differently"),
+            MissingAssocConstraint => write!(f, "missing associated item constraint"),
+            InherentAssociatedType => write!(f, "inherent associated type"),
+            UnresolvedProjection => write!(f, "unresolved projection"),
+            TupleShape => write!(f, "tuple shape"),
+            TupleLengthDiffers => write!(f, "tuple length differs"),
+            ArrayType => write!(f, "array type"),
+            SliceType => write!(f, "slice type"),
+            SliceAndArray => write!(f, "slice and array"),
+            MutabilityDiffers => write!(f, "mutability differs"),
+            PointerWrapper => write!(f, "pointer/reference wrapper"),
+            OpaqueTypeAndTraitObject => write!(f, "opaque type and trait object"),
+            ClosureTraitMatches => write!(f, "closure trait matches"),
+            ClosureTraitDiffers => write!(f, "closure trait differs"),
+            FunctionPointer => write!(f, "function pointer"),
+            FunctionPointerAndClosure => write!(f, "function pointer and closure"),
+            ArgumentCountDiffers { query, candidate } => {
+                write!(f, "argument count differs ({} vs {})", query, candidate)
+            }
+            DerefCoercion => write!(f, "deref coercion"),
+            AsRefConversion => write!(f, "AsRef conversion"),
+            BorrowConversion => write!(f, "Borrow conversion"),
+            PrimitiveMatches => write!(f, "primitive matches"),
+            PrimitiveDiffers => write!(f, "primitive differs"),
+            TypeMismatch { query, candidate } => {
+                write!(f, "type mismatch, `{}` and `{}`", query, candidate)
+            }
+        }
+    }
+}
+
 pub trait Compare<Rhs> {
     fn compare(
         &self,
         rhs: &Rhs,
         krate: &Crate,
+        profile: &ScoringProfile,
         generics: &mut Generics,
         substs: &mut HashMap<String, Type>,
     ) -> Vec<Similarity>;
 }
 
 impl Compare<Item> for Query {
-    #[instrument(name = "cmp_query", skip(self, item, krate, generics, substs), fields(query = %self, item = %item))]
+    #[instrument(name = "cmp_query", skip(self, item, krate, profile, generics, substs), fields(query = %self, item = %item))]
     fn compare(
         &self,
         item: &Item,
         krate: &Crate,
+        profile: &ScoringProfile,
         generics: &mut Generics,
         substs: &mut HashMap<String, Type>,
     ) -> Vec<Similarity> {
-        let mut sims = vec![];
+        explain::record(
+            profile,
+            || self.to_string(),
+            || {
+                let mut sims = vec![];
 
-        match (&self.name, &item.name) {
-            (Some(q), Some(i)) => sims.append(&mut q.compare(i, krate, generics, substs)),
-            (Some(_), None) => sims.push(Discrete {
-                kind: Different,
-                reason: "missing item name".to_string(),
-            }),
-            _ => {}
-        }
-        trace!(?sims);
+                match (&self.name, &item.name) {
+                    (Some(q), Some(i)) => {
+                        let path = item_path(item, krate, generics);
+                        match compare_qualifier(q, path.as_deref()) {
+                            Some(
+                                sim @ Discrete {
+                                    kind: Different, ..
+                                },
+                            ) => return vec![sim],
+                            Some(sim) => sims.push(sim),
+                            None => {}
+                        }
+                        sims.append(&mut q.compare(i, krate, profile, generics, substs))
+                    }
+                    (Some(_), None) => sims.push(Discrete {
+                        kind: Different,
+                        reason: SimilarityReason::MissingItemName,
+                        category: Category::Name,
+                    }),
+                    _ => {}
+                }
+                trace!(?sims);
 
-        if let Some(ref kind) = self.kind {
-            sims.append(&mut kind.compare(&item.inner, krate, generics, substs));
-            trace!(?sims);
-        }
+                if let Some(ref kind) = self.kind {
+                    sims.append(&mut kind.compare(&item.inner, krate, profile, generics, substs));
+                    trace!(?sims);
+                }
 
-        sims
+                sims
+            },
+        )
     }
 }
 
 impl Compare<String> for Symbol {
-    #[instrument(name = "cmp_symbol", skip(self, symbol), fields(self = %self, symbol = %symbol))]
+    #[instrument(name = "cmp_symbol", skip(self, symbol, profile), fields(self = %self, symbol = %symbol))]
     fn compare(
         &self,
         symbol: &String,
         _: &Crate,
+        profile: &ScoringProfile,
         _: &mut Generics,
         _: &mut HashMap<String, Type>,
     ) -> Vec<Similarity> {
-        use std::cmp::max;
-
+        // Qualifiers are checked against the item's path by `compare_qualifier`.
+        let query = self.split("::").last().unwrap(); // SAFETY: `self` is not empty.
         let symbol = symbol.split("::").last().unwrap(); // SAFETY: `symbol` is not empty.
         vec![Continuous {
-            value: levenshtein(self, symbol) as f32 / max(self.len(), symbol.len()) as f32,
-            reason: "symbol name distance".to_string(),
+            value: profile.names.distance(query, symbol),
+            reason: SimilarityReason::SymbolNameDistance,
+            category: Category::Name,
         }]
     }
 }
 
+/// Checks the qualifier of a path-qualified query name, e.g. `io` in `io::Error`, against the
+/// fully qualified `path` of an item. Returns `None` for unqualified names, which are matched
+/// by their last segment only.
+fn compare_qualifier(q: &str, path: Option<&[String]>) -> Option<Similarity> {
+    let (qualifier, _) = q.rsplit_once("::")?;
+    let Some((_, modules)) = path.and_then(|path| path.split_last()) else {
+        return Some(Discrete {
+            kind: Subequal,
+            reason: SimilarityReason::QualifiedPathUnknown,
+            category: Category::Qualifier,
+        });
+    };
+
+    // The standard library crates re-export one another, so they are interchangeable.
+    let is_std = |segment: &str| matches!(segment, "std" | "core" | "alloc");
+    let mut modules = modules.iter();
+    let matches = qualifier.split("::").all(|segment| {
+        modules.any(|module| module == segment || (is_std(segment) && is_std(module)))
+    });
+
+    Some(if matches {
+        Discrete {
+            kind: Equivalent,
+            reason: SimilarityReason::QualifiedPathMatches,
+            category: Category::Qualifier,
+        }
+    } else {
+        Discrete {
+            kind: Different,
+            reason: SimilarityReason::QualifiedPathDiffers,
+            category: Category::Qualifier,
+        }
+    })
+}
+
+/// The fully qualified path of `item`. Methods, which have no path of their own, are qualified
+/// by the type or trait `Self` stands for.
+fn item_path(item: &Item, krate: &Crate, generics: &Generics) -> Option<Vec<String>> {
+    if let Some(summary) = krate.paths.get(&item.id) {
+        return Some(summary.path.clone());
+    }
+
+    let owner = generics.where_predicates.iter().find_map(|p| match p {
+        types::WherePredicate::EqPredicate {
+            lhs: types::Type::Generic(lhs),
+            rhs: types::Term::Type(types::Type::ResolvedPath(path)),
+        } if lhs == "Self" => Some(path.id),
+        types::WherePredicate::BoundPredicate {
+            type_: types::Type::Generic(type_),
+            bounds,
+            ..
+        } if type_ == "Self" => bounds.iter().find_map(|bound| match bound {
+            types::GenericBound::TraitBound { trait_, .. } => Some(trait_.id),
+            _ => None,
+        }),
+        _ => None,
+    })?;
+
+    let mut path = krate.paths.get(&owner)?.path.clone();
+    path.push(item.name.clone()?);
+    Some(path)
+}
+
+/// The fully qualified path of the item `path` refers to, falling back to the path as written.
+fn resolved_path(path: &types::Path, krate: &Crate) -> Option<Vec<String>> {
+    match krate.paths.get(&path.id) {
+        Some(summary) => Some(summary.path.clone()),
+        None if path.path.contains("::") => {
+            Some(path.path.split("::").map(str::to_owned).collect())
+        }
+        None => None,
+    }
+}
+
 impl Compare<types::ItemEnum> for QueryKind {
-    #[instrument(name = "cmp_kind", skip(krate, generics, substs))]
+    #[instrument(name = "cmp_kind", skip(krate, profile, generics, substs))]
     fn compare(
         &self,
         kind: &types::ItemEnum,
         krate: &Crate,
+        profile: &ScoringProfile,
         generics: &mut Generics,
         substs: &mut HashMap<String, Type>,
     ) -> Vec<Similarity> {
@@ -156,14 +644,269 @@ impl Compare<types::ItemEnum> for QueryKind {
         use QueryKind::*;
 
         match (self, kind) {
-            (FunctionQuery(q), Function(i)) => q.compare(i, krate, generics, substs),
-            // (FunctionQuery(q), Method(i)) => q.compare(i, krate, generics, substs),
+            (FunctionQuery(q), Function(i)) => q.compare(i, krate, profile, generics, substs),
+            // (FunctionQuery(q), Method(i)) => q.compare(i, krate, profile, generics, substs),
             (FunctionQuery(_), _) => vec![Discrete {
                 kind: Different,
-                reason: "query expects function".to_string(),
+                reason: SimilarityReason::QueryExpects(ItemKind::Function),
+                category: Category::Other,
+            }],
+            (MacroQuery(q), Macro(i)) => q.compare(i, krate, profile, generics, substs),
+            (MacroQuery(q), ProcMacro(i)) => q.compare(i, krate, profile, generics, substs),
+            (MacroQuery(_), _) => vec![Discrete {
+                kind: Different,
+                reason: SimilarityReason::QueryExpects(ItemKind::Macro),
+                category: Category::Other,
             }],
+            (StructQuery(q), Struct(types::Struct { generics: i, .. }))
+            | (EnumQuery(q), Enum(types::Enum { generics: i, .. }))
+            | (UnionQuery(q), Union(types::Union { generics: i, .. }))
+            | (TraitQuery(q), Trait(types::Trait { generics: i, .. })) => {
+                q.compare(i, krate, profile, generics, substs)
+            }
+            (TypeAliasQuery(q), TypeAlias(i)) => {
+                let mut sims = q.compare(&i.generics, krate, profile, generics, substs);
+                if let Some(ref aliased) = q.aliased {
+                    generics.params.extend(i.generics.params.iter().cloned());
+                    sims.append(&mut aliased.compare(&i.type_, krate, profile, generics, substs));
+                }
+                sims
+            }
+            (StructQuery(_), _) => vec![Discrete {
+                kind: Different,
+                reason: SimilarityReason::QueryExpects(ItemKind::Struct),
+                category: Category::Other,
+            }],
+            (EnumQuery(_), _) => vec![Discrete {
+                kind: Different,
+                reason: SimilarityReason::QueryExpects(ItemKind::Enum),
+                category: Category::Other,
+            }],
+            (UnionQuery(_), _) => vec![Discrete {
+                kind: Different,
+                reason: SimilarityReason::QueryExpects(ItemKind::Union),
+                category: Category::Other,
+            }],
+            (TraitQuery(_), _) => vec![Discrete {
+                kind: Different,
+                reason: SimilarityReason::QueryExpects(ItemKind::Trait),
+                category: Category::Other,
+            }],
+            (TypeAliasQuery(_), _) => vec![Discrete {
+                kind: Different,
+                reason: SimilarityReason::QueryExpects(ItemKind::TypeAlias),
+                category: Category::Other,
+            }],
+        }
+    }
+}
+
+impl Compare<String> for crate::query::Macro {
+    #[instrument(name = "cmp_macro", skip(self, matchers, profile), fields(self = ?self))]
+    fn compare(
+        &self,
+        matchers: &String,
+        _: &Crate,
+        profile: &ScoringProfile,
+        _: &mut Generics,
+        _: &mut HashMap<String, Type>,
+    ) -> Vec<Similarity> {
+        let mut sims = vec![];
+
+        match self.kind {
+            Some(MacroKind::Bang) => sims.push(Discrete {
+                kind: Equivalent,
+                reason: SimilarityReason::MacroKindMatches,
+                category: Category::Other,
+            }),
+            Some(_) => sims.push(Discrete {
+                kind: Different,
+                reason: SimilarityReason::MacroKindDiffers,
+                category: Category::Other,
+            }),
+            None => {}
+        }
+
+        if let Some(ref fragments) = self.fragments {
+            // Pick the arm whose matcher fits the requested fragments best.
+            let best = macro_arms(matchers)
+                .into_iter()
+                .map(|arm| compare_fragments(fragments, arm))
+                .min_by(|a, b| {
+                    let score =
+                        |sims: &Vec<Similarity>| Similarities(sims.clone()).score_with(profile);
+                    score(a).total_cmp(&score(b))
+                });
+            match best {
+                Some(mut best) => sims.append(&mut best),
+                None => sims.push(Discrete {
+                    kind: Different,
+                    reason: SimilarityReason::NoMacroMatcher,
+                    category: Category::Other,
+                }),
+            }
+        }
+
+        sims
+    }
+}
+
+impl Compare<Generics> for TypeDef {
+    #[instrument(name = "cmp_type_def", skip(self, generics), fields(self = %self))]
+    fn compare(
+        &self,
+        generics: &Generics,
+        _: &Crate,
+        _: &ScoringProfile,
+        _: &mut Generics,
+        _: &mut HashMap<String, Type>,
+    ) -> Vec<Similarity> {
+        let Some(arity) = self.arity else {
+            return vec![];
+        };
+
+        // Lifetimes and compiler-introduced `impl Trait` parameters are not spelled out in queries.
+        let params = generics
+            .params
+            .iter()
+            .filter(|p| match p.kind {
+                types::GenericParamDefKind::Lifetime { .. } => false,
+                types::GenericParamDefKind::Type { is_synthetic, .. } => !is_synthetic,
+                types::GenericParamDefKind::Const { .. } => true,
+            })
+            .count();
+
+        if arity == params {
+            vec![Discrete {
+                kind: Equivalent,
+                reason: SimilarityReason::GenericArityMatches,
+                category: Category::Arity,
+            }]
+        } else {
+            vec![
+                Discrete {
+                    kind: Different,
+                    reason: SimilarityReason::GenericArityDiffers,
+                    category: Category::Arity,
+                };
+                usize::abs_diff(arity, params)
+            ]
+        }
+    }
+}
+
+impl Compare<types::ProcMacro> for crate::query::Macro {
+    #[instrument(name = "cmp_proc_macro", skip(self, proc_macro), fields(self = ?self, kind = ?proc_macro.kind))]
+    fn compare(
+        &self,
+        proc_macro: &types::ProcMacro,
+        _: &Crate,
+        _: &ScoringProfile,
+        _: &mut Generics,
+        _: &mut HashMap<String, Type>,
+    ) -> Vec<Similarity> {
+        let mut sims = vec![];
+
+        if let Some(kind) = self.kind {
+            let matches = matches!(
+                (kind, proc_macro.kind),
+                (MacroKind::Bang, types::MacroKind::Bang)
+                    | (MacroKind::Attr, types::MacroKind::Attr)
+                    | (MacroKind::Derive, types::MacroKind::Derive)
+            );
+            sims.push(if matches {
+                Discrete {
+                    kind: Equivalent,
+                    reason: SimilarityReason::MacroKindMatches,
+                    category: Category::Other,
+                }
+            } else {
+                Discrete {
+                    kind: Different,
+                    reason: SimilarityReason::MacroKindDiffers,
+                    category: Category::Other,
+                }
+            });
+        }
+
+        if self.fragments.is_some() {
+            sims.push(Discrete {
+                kind: Different,
+                reason: SimilarityReason::ProcMacroHasNoMatcher,
+                category: Category::Other,
+            });
+        }
+
+        sims
+    }
+}
+
+/// Split the `macro_rules!` source rustdoc renders into the fragment specifiers of each arm.
+///
+/// Rustdoc elides every transcriber to `{ ... }`, so any `$name:fragment` preceding the n-th
+/// `=>` belongs to the matcher of the n-th arm.
+fn macro_arms(source: &str) -> Vec<Vec<&str>> {
+    let mut arms: Vec<Vec<&str>> = source
+        .split("=>")
+        .map(|segment| {
+            segment
+                .split('$')
+                .skip(1)
+                .filter_map(|var| {
+                    let (name, rest) = var.split_once(':')?;
+                    if name.is_empty() || !name.chars().all(|c| c.is_alphanumeric() || c == '_') {
+                        return None;
+                    }
+                    let end = rest
+                        .find(|c: char| !(c.is_alphanumeric() || c == '_'))
+                        .unwrap_or(rest.len());
+                    Some(&rest[..end])
+                })
+                .collect()
+        })
+        .collect();
+    // The segment after the last `=>` only holds a transcriber.
+    arms.pop();
+    arms
+}
+
+fn compare_fragments(query: &[Symbol], arm: Vec<&str>) -> Vec<Similarity> {
+    let mut remaining = arm;
+    let mut sims = vec![];
+
+    for fragment in query {
+        match remaining.iter().position(|f| f == fragment) {
+            Some(idx) => {
+                remaining.remove(idx);
+                sims.push(Discrete {
+                    kind: Equivalent,
+                    reason: SimilarityReason::FragmentSpecifierMatches,
+                    category: Category::Other,
+                });
+            }
+            None => sims.push(Discrete {
+                kind: Different,
+                reason: SimilarityReason::MissingFragmentSpecifier,
+                category: Category::Other,
+            }),
         }
     }
+    for _ in remaining {
+        sims.push(Discrete {
+            kind: Subequal,
+            reason: SimilarityReason::ExtraFragmentSpecifier,
+            category: Category::Other,
+        });
+    }
+    if sims.is_empty() {
+        sims.push(Discrete {
+            kind: Equivalent,
+            reason: SimilarityReason::NoFragmentSpecifiers,
+            category: Category::Other,
+        });
+    }
+
+    sims
 }
 
 impl Compare<Qualifier> for Qualifier {
@@ -172,6 +915,7 @@ impl Compare<Qualifier> for Qualifier {
         &self,
         qualifer: &Qualifier,
         _: &Crate,
+        _: &ScoringProfile,
         _: &mut Generics,
         _: &mut HashMap<String, Type>,
     ) -> Vec<Similarity> {
@@ -180,12 +924,14 @@ impl Compare<Qualifier> for Qualifier {
         if self == qualifer {
             sims.push(Discrete {
                 kind: Equivalent,
-                reason: "qualifier matched".to_string(),
+                reason: SimilarityReason::QualifierMatches,
+                category: Category::Qualifier,
             });
         } else {
             sims.push(Discrete {
                 kind: Different,
-                reason: "qualifier different".to_string(),
+                reason: SimilarityReason::QualifierDiffers,
+                category: Category::Qualifier,
             });
         }
 
@@ -193,11 +939,12 @@ impl Compare<Qualifier> for Qualifier {
     }
 }
 impl Compare<types::Function> for Function {
-    #[instrument(name = "cmp_fn", skip(self, function, krate, generics, substs), fields(decl = %self.decl, qualifiers = ?self.qualifiers, function = ?function, generics = ?generics, substs = ?substs))]
+    #[instrument(name = "cmp_fn", skip(self, function, krate, profile, generics, substs), fields(decl = %self.decl, qualifiers = ?self.qualifiers, function = ?function, generics = ?generics, substs = ?substs))]
     fn compare(
         &self,
         function: &types::Function,
         krate: &Crate,
+        profile: &ScoringProfile,
         generics: &mut Generics,
         substs: &mut HashMap<String, Type>,
     ) -> Vec<Similarity> {
@@ -222,129 +969,654 @@ impl Compare<types::Function> for Function {
             .cloned()
             .collect::<HashSet<_>>();
 
-        for _ in missing_qualifiers {
+        for qualifier in missing_qualifiers {
             sims.push(Discrete {
                 kind: Different,
-                reason: "missing qualifier".to_string(),
+                reason: SimilarityReason::MissingQualifier(qualifier),
+                category: Category::Qualifier,
             });
         }
-        for _ in extra_qualifiers {
+        for qualifier in extra_qualifiers {
             sims.push(Discrete {
                 kind: Different,
-                reason: "extra qualifier".to_string(),
+                reason: SimilarityReason::ExtraQualifier(qualifier),
+                category: Category::Qualifier,
+            });
+        }
+
+        // At equal match quality, generic signatures matching nearly anything go last.
+        let constrained = self.decl.inputs.is_some()
+            || self.decl.output.is_some()
+            || self.decl.receiver.is_some();
+        let looseness = looseness(&function.sig, generics);
+        if constrained && looseness > 0.0 {
+            sims.push(Continuous {
+                value: looseness,
+                reason: SimilarityReason::LooselyTypedSignature,
+                category: Category::Specificity,
             });
         }
 
-        sims.extend(self.decl.compare(&function.sig, krate, generics, substs));
+        sims.extend(
+            self.decl
+                .compare(&function.sig, krate, profile, generics, substs),
+        );
+
+        // Bounds can only be checked once the signature has bound query generics to candidate ones.
+        let candidate = generics.clone();
+        sims.extend(
+            self.generics
+                .compare(&candidate, krate, profile, generics, substs),
+        );
         sims
     }
 }
 
-impl Compare<types::FunctionSignature> for FnDecl {
-    #[instrument(name = "cmp_sig", skip(self, decl, krate, generics, substs), fields(decl = %self, sig = %decl))]
+/// The share of `sig` left open by generics, from 0 for concrete signatures to 1 for ones
+/// like `fn<T>(T) -> T`. Only generics without bounds leave a type open.
+fn looseness(sig: &types::FunctionSignature, generics: &Generics) -> f32 {
+    let (concrete, total) = sig
+        .inputs
+        .iter()
+        .map(|(_, type_)| type_)
+        .chain(&sig.output)
+        .map(|type_| specificity(type_, generics))
+        .fold((0.0, 0), |(c, t), (concrete, total)| {
+            (c + concrete, t + total)
+        });
+    if total == 0 {
+        0.0
+    } else {
+        1.0 - concrete / total as f32
+    }
+}
+
+/// How many parts `type_` has, and how concrete they are summed up.
+fn specificity(type_: &types::Type, generics: &Generics) -> (f32, usize) {
+    use types::Type;
+    let sum = |types: &mut dyn Iterator<Item = &Type>| {
+        types
+            .map(|type_| specificity(type_, generics))
+            .fold((0.0, 0), |(c, t), (concrete, total)| {
+                (c + concrete, t + total)
+            })
+    };
+    match type_ {
+        Type::Generic(name) if name == "Self" || is_bounded(name, generics) => (1.0, 1),
+        Type::Generic(_) | Type::Infer => (0.0, 1),
+        Type::ImplTrait(_)
+        | Type::DynTrait(_)
+        | Type::QualifiedPath { .. }
+        | Type::Primitive(_) => (1.0, 1),
+        Type::ResolvedPath(path) => {
+            let (concrete, total) = match path.args.as_deref() {
+                Some(types::GenericArgs::AngleBracketed { args, .. }) => {
+                    sum(&mut args.iter().filter_map(|arg| match arg {
+                        types::GenericArg::Type(type_) => Some(type_),
+                        _ => None,
+                    }))
+                }
+                Some(types::GenericArgs::Parenthesized { inputs, output }) => {
+                    sum(&mut inputs.iter().chain(output))
+                }
+                _ => (0.0, 0),
+            };
+            (concrete + 1.0, total + 1)
+        }
+        Type::Tuple(types) => sum(&mut types.iter()),
+        Type::Slice(type_)
+        | Type::Array { type_, .. }
+        | Type::Pat { type_, .. }
+        | Type::RawPointer { type_, .. }
+        | Type::BorrowedRef { type_, .. } => specificity(type_, generics),
+        Type::FunctionPointer(f) => sum(&mut f
+            .sig
+            .inputs
+            .iter()
+            .map(|(_, type_)| type_)
+            .chain(&f.sig.output)),
+    }
+}
+
+/// Whether the generic `name` is bounded by its declaration or a where predicate.
+fn is_bounded(name: &str, generics: &Generics) -> bool {
+    let params = generics.params.iter().any(|p| {
+        p.name == name
+            && matches!(&p.kind, types::GenericParamDefKind::Type { bounds, .. } if !bounds.is_empty())
+    });
+    let predicates = generics.where_predicates.iter().any(|p| {
+        matches!(p, types::WherePredicate::BoundPredicate { type_: types::Type::Generic(n), bounds, .. } if n == name && !bounds.is_empty())
+    });
+    params || predicates
+}
+
+impl Compare<Generics> for crate::query::Generics {
+    #[instrument(name = "cmp_generics", skip(self, candidate, krate, profile, generics, substs), fields(self = %self))]
     fn compare(
         &self,
-        decl: &types::FunctionSignature,
+        candidate: &Generics,
         krate: &Crate,
+        profile: &ScoringProfile,
         generics: &mut Generics,
         substs: &mut HashMap<String, Type>,
     ) -> Vec<Similarity> {
         let mut sims = vec![];
 
-        if let Some(ref inputs) = self.inputs {
-            inputs.iter().enumerate().for_each(|(idx, q)| {
-                if let Some(i) = decl.inputs.get(idx) {
-                    sims.append(&mut q.compare(i, krate, generics, substs))
-                }
-            });
+        let params = self
+            .params
+            .iter()
+            .map(|p| (Type::Generic(p.name.clone()), &p.bounds));
+        let predicates = self.where_predicates.iter().map(|p| match p {
+            WherePredicate::BoundPredicate { type_, bounds } => (type_.clone(), bounds),
+        });
 
-            if inputs.len() != decl.inputs.len() {
-                let abs_diff = usize::abs_diff(inputs.len(), decl.inputs.len());
-                sims.append(&mut vec![
-                    Discrete {
-                        kind: Different,
-                        reason: "argument count differs".to_string()
-                    };
-                    abs_diff
-                ])
-            } else if inputs.is_empty() && decl.inputs.is_empty() {
-                sims.push(Discrete {
-                    kind: Equivalent,
-                    reason: "no arguments".to_string(),
-                });
+        for (type_, query_bounds) in params.chain(predicates) {
+            if query_bounds.is_empty() {
+                continue;
             }
-            trace!(?sims);
-        }
 
-        if let Some(ref output) = self.output {
-            sims.append(&mut output.compare(&decl.output, krate, generics, substs));
-            trace!(?sims);
+            let bounds = bounds_on(&type_, candidate, krate, profile, substs);
+            for bound in query_bounds {
+                sims.append(&mut bound.compare(&bounds, krate, profile, generics, substs));
+            }
         }
 
         sims
     }
 }
 
-impl Compare<(String, types::Type)> for Argument {
-    #[instrument(name = "cmp_arg", skip(self, arg, krate, generics, substs), fields(self_name = ?self.name, self_has_type = %self.ty.as_ref().map(|t| t.to_string()).unwrap_or("<NONE>".to_string()), arg_name = %arg.0, arg_type = %arg.1))]
-    fn compare(
-        &self,
-        arg: &(String, types::Type),
-        krate: &Crate,
-        generics: &mut Generics,
-        substs: &mut HashMap<String, Type>,
-    ) -> Vec<Similarity> {
-        let mut sims = vec![];
-
-        if let Some(ref name) = self.name {
-            sims.append(&mut name.compare(&arg.0, krate, generics, substs));
-            trace!(?sims);
+/// Collects every bound `candidate` places on the type the query refers to as `type_`, from both
+/// the generic parameter list and the where predicates.
+fn bounds_on(
+    type_: &Type,
+    candidate: &Generics,
+    krate: &Crate,
+    profile: &ScoringProfile,
+    substs: &HashMap<String, Type>,
+) -> Vec<types::GenericBound> {
+    let refers_to = |i: &types::Type| {
+        if let types::Type::Generic(name) = i {
+            let is_self =
+                matches!(type_, Type::UnresolvedPath { name, args: None } if name == "Self");
+            if is_self && name == "Self" {
+                return true;
+            }
+            // Unbound candidate generics would otherwise be substituted by anything.
+            if !substs.contains_key(name) && name != "Self" {
+                return false;
+            }
         }
+        let sims = explain::quiet(|| {
+            type_.compare(
+                i,
+                krate,
+                profile,
+                &mut candidate.clone(),
+                &mut substs.clone(),
+            )
+        });
+        !sims.is_empty() && sims.iter().all(|sim| sim.score() == 0.0)
+    };
 
-        if let Some(ref type_) = self.ty {
-            sims.append(&mut type_.compare(&arg.1, krate, generics, substs));
-            trace!(?sims);
+    let params = candidate.params.iter().filter_map(|p| match p.kind {
+        types::GenericParamDefKind::Type { ref bounds, .. }
+            if refers_to(&types::Type::Generic(p.name.clone())) =>
+        {
+            Some(bounds)
+        }
+        _ => None,
+    });
+    let predicates = candidate.where_predicates.iter().filter_map(|p| match p {
+        types::WherePredicate::BoundPredicate { type_, bounds, .. } if refers_to(type_) => {
+            Some(bounds)
         }
+        _ => None,
+    });
 
-        sims
-    }
+    params.chain(predicates).flatten().cloned().collect()
 }
 
-impl Compare<Option<types::Type>> for FnRetTy {
-    #[instrument(name = "cmp_ret", skip(self, ret_ty, krate, generics, substs), fields(expected = ?self, actual = ?ret_ty))]
+impl Compare<Vec<types::GenericBound>> for GenericBound {
+    #[instrument(name = "cmp_bound", skip(self, bounds, krate, profile, generics, substs), fields(self = %self))]
     fn compare(
         &self,
-        ret_ty: &Option<types::Type>,
+        bounds: &Vec<types::GenericBound>,
         krate: &Crate,
+        profile: &ScoringProfile,
         generics: &mut Generics,
         substs: &mut HashMap<String, Type>,
     ) -> Vec<Similarity> {
-        match (self, ret_ty) {
-            (FnRetTy::Return(q), Some(i)) => q.compare(i, krate, generics, substs),
-            (FnRetTy::DefaultReturn, None) => vec![Discrete {
-                kind: Equivalent,
-                reason: "unit return".to_string(),
-            }],
+        let GenericBound::TraitBound(Type::UnresolvedPath { name, args }) = self else {
+            return vec![Discrete {
+                kind: Different,
+                reason: SimilarityReason::UnsupportedTraitBound,
+                category: Category::Generic,
+            }];
+        };
+
+        let last = name.split("::").last();
+        let trait_ = bounds.iter().find_map(|bound| match bound {
+            types::GenericBound::TraitBound { trait_, .. }
+                if trait_.path.split("::").last() == last
+                    && !matches!(
+                        compare_qualifier(name, resolved_path(trait_, krate).as_deref()),
+                        Some(Discrete {
+                            kind: Different,
+                            ..
+                        })
+                    ) =>
+            {
+                Some(trait_)
+            }
+            _ => None,
+        });
+
+        // Closures implementing a stricter `Fn*` trait satisfy the looser ones too.
+        let (trait_, kind) = match trait_ {
+            Some(trait_) => (Some(trait_), Equivalent),
+            None if last.is_some_and(|last| FN_TRAITS.contains(&last)) => (
+                bounds.iter().find_map(|bound| match bound {
+                    types::GenericBound::TraitBound { trait_, .. }
+                        if trait_
+                            .path
+                            .split("::")
+                            .last()
+                            .is_some_and(|name| FN_TRAITS.contains(&name)) =>
+                    {
+                        Some(trait_)
+                    }
+                    _ => None,
+                }),
+                Subequal,
+            ),
+            None => (None, Different),
+        };
+
+        match trait_ {
+            Some(trait_) => {
+                let mut sims = vec![Discrete {
+                    kind,
+                    reason: SimilarityReason::TraitBoundMatches,
+                    category: Category::Generic,
+                }];
+                sims.append(&mut compare_generic_args(
+                    args,
+                    &trait_.args,
+                    krate,
+                    profile,
+                    generics,
+                    substs,
+                ));
+                sims
+            }
+            None => vec![Discrete {
+                kind: Different,
+                reason: SimilarityReason::MissingTraitBound,
+                category: Category::Generic,
+            }],
+        }
+    }
+}
+
+impl Compare<types::FunctionSignature> for FnDecl {
+    #[instrument(name = "cmp_sig", skip(self, decl, krate, profile, generics, substs), fields(decl = %self, sig = %decl))]
+    fn compare(
+        &self,
+        decl: &types::FunctionSignature,
+        krate: &Crate,
+        profile: &ScoringProfile,
+        generics: &mut Generics,
+        substs: &mut HashMap<String, Type>,
+    ) -> Vec<Similarity> {
+        let mut sims = vec![];
+
+        // The receiver is matched on its own, so the remaining arguments line up with `inputs`.
+        let mut candidate = &decl.inputs[..];
+        if let Some(ref receiver) = self.receiver {
+            match candidate.split_first() {
+                Some(((name, self_), rest)) if name == "self" => {
+                    sims.append(&mut explain::record(
+                        profile,
+                        || "receiver".to_string(),
+                        || receiver.compare(self_, krate, profile, generics, substs),
+                    ));
+                    candidate = rest;
+                }
+                _ => sims.push(Discrete {
+                    kind: Different,
+                    reason: SimilarityReason::MissingReceiver,
+                    category: Category::Argument,
+                }),
+            }
+            trace!(?sims);
+        }
+
+        let start = sims.len();
+        if let Some(ref inputs) = self.inputs {
+            let order = if self.unordered {
+                assign_arguments(inputs, candidate, krate, profile, generics, substs)
+            } else {
+                (0..inputs.len())
+                    .map(|idx| (idx < candidate.len()).then_some(idx))
+                    .collect()
+            };
+            for (k, (q, idx)) in inputs.iter().zip(&order).enumerate() {
+                if let Some(i) = idx.and_then(|idx| candidate.get(idx)) {
+                    sims.append(&mut explain::record(
+                        profile,
+                        || format!("argument {}", k),
+                        || q.compare(i, krate, profile, generics, substs),
+                    ))
+                }
+            }
+            if order
+                .iter()
+                .enumerate()
+                .any(|(k, idx)| idx.is_some_and(|idx| idx != k))
+            {
+                sims.push(Discrete {
+                    kind: Subequal,
+                    reason: SimilarityReason::ArgumentsReordered(order.clone()),
+                    category: Category::Argument,
+                });
+            }
+
+            // Every argument of the shorter side is assigned, so the others are in excess.
+            let missing = (0..inputs.len())
+                .filter(|&index| order[index].is_none())
+                .map(|index| SimilarityReason::MissingArgument { index });
+            let extra = (0..candidate.len())
+                .filter(|index| !order.contains(&Some(*index)))
+                .map(|index| SimilarityReason::ExtraArgument { index });
+            sims.extend(missing.chain(extra).map(|reason| Discrete {
+                kind: Different,
+                reason,
+                category: Category::Arity,
+            }));
+            if inputs.is_empty() && candidate.is_empty() {
+                sims.push(Discrete {
+                    kind: Equivalent,
+                    reason: SimilarityReason::NoArguments,
+                    category: Category::Arity,
+                });
+            }
+            trace!(?sims);
+        }
+        let outputs = sims.len();
+
+        if let Some(ref output) = self.output {
+            sims.append(&mut explain::record(
+                profile,
+                || "return type".to_string(),
+                || output.compare(&decl.output, krate, profile, generics, substs),
+            ));
+            trace!(?sims);
+        }
+
+        // Uncategorized type similarities are attributed to where they were found.
+        for (k, sim) in sims.iter_mut().enumerate().skip(start) {
+            if sim.category() == Category::Other {
+                sim.set_category(if k < outputs {
+                    Category::Argument
+                } else {
+                    Category::Return
+                });
+            }
+        }
+
+        sims
+    }
+}
+
+impl Compare<types::Type> for Receiver {
+    #[instrument(name = "cmp_receiver", skip(self, self_, krate, profile, generics, substs), fields(self = ?self, self_ = ?self_))]
+    fn compare(
+        &self,
+        self_: &types::Type,
+        krate: &Crate,
+        profile: &ScoringProfile,
+        generics: &mut Generics,
+        substs: &mut HashMap<String, Type>,
+    ) -> Vec<Similarity> {
+        let mut sims = vec![];
+
+        let (mode, type_) = match self_ {
+            types::Type::BorrowedRef {
+                is_mutable, type_, ..
+            } => (
+                if *is_mutable {
+                    ReceiverMode::RefMut
+                } else {
+                    ReceiverMode::Ref
+                },
+                type_.as_ref(),
+            ),
+            _ => (ReceiverMode::Value, self_),
+        };
+
+        if let Some(ref m) = self.mode {
+            if *m == mode {
+                sims.push(Discrete {
+                    kind: Equivalent,
+                    reason: SimilarityReason::ReceiverModeMatches,
+                    category: Category::Argument,
+                });
+            } else {
+                sims.push(Discrete {
+                    kind: Different,
+                    reason: SimilarityReason::ReceiverModeDiffers {
+                        query: *m,
+                        candidate: mode,
+                    },
+                    category: Category::Argument,
+                });
+            }
+        }
+
+        // `Self` resolves to the `for_` type of the impl the method is in.
+        if let Some(ref ty) = self.type_ {
+            sims.append(&mut ty.compare(type_, krate, profile, generics, substs));
+        }
+
+        sims
+    }
+}
+
+/// Added to the cost of every pair that is out of the written order, so that ties are broken in
+/// its favor without outweighing any actual difference between pairs.
+const REORDER_COST: f64 = 1e-6;
+
+/// For each query argument, the position of the argument of `decl` it matches best, such that
+/// no argument is matched twice. `None` is left for query arguments in excess.
+///
+/// Pairs are scored independently of each other under `profile`, so generics substituted by one
+/// pair don't affect the others, and the cheapest assignment is found with the Hungarian
+/// algorithm.
+fn assign_arguments(
+    inputs: &[Argument],
+    decl: &[(String, types::Type)],
+    krate: &Crate,
+    profile: &ScoringProfile,
+    generics: &Generics,
+    substs: &HashMap<String, Type>,
+) -> Vec<Option<usize>> {
+    // Pad to a square matrix, where pairs with a missing query or candidate argument are free.
+    let n = max(inputs.len(), decl.len());
+    let mut costs = vec![vec![0.0; n]; n];
+    for (k, q) in inputs.iter().enumerate() {
+        for (idx, i) in decl.iter().enumerate() {
+            let cost = Similarities(explain::quiet(|| {
+                q.compare(
+                    i,
+                    krate,
+                    profile,
+                    &mut generics.clone(),
+                    &mut substs.clone(),
+                )
+            }))
+            .score_with(profile);
+            costs[k][idx] = cost as f64 + if k == idx { 0.0 } else { REORDER_COST };
+        }
+    }
+
+    let assignment = hungarian(&costs);
+    assignment[..inputs.len()]
+        .iter()
+        .map(|&idx| (idx < decl.len()).then_some(idx))
+        .collect()
+}
+
+/// The column assigned to each row of the square matrix `costs` so that the sum of their costs
+/// is minimal, in O(n³).
+fn hungarian(costs: &[Vec<f64>]) -> Vec<usize> {
+    let n = costs.len();
+    // Potentials of rows and columns, and the row matched to each column, all 1-indexed so that
+    // column 0 stands for the row being added.
+    let (mut u, mut v) = (vec![0.0; n + 1], vec![0.0; n + 1]);
+    let mut matched = vec![0; n + 1];
+    let mut way = vec![0; n + 1];
+    for row in 1..=n {
+        matched[0] = row;
+        let mut col = 0;
+        let mut min = vec![f64::INFINITY; n + 1];
+        let mut used = vec![false; n + 1];
+        while matched[col] != 0 {
+            used[col] = true;
+            let (r, mut delta, mut next) = (matched[col], f64::INFINITY, 0);
+            for c in 1..=n {
+                if used[c] {
+                    continue;
+                }
+                let reduced = costs[r - 1][c - 1] - u[r] - v[c];
+                if reduced < min[c] {
+                    min[c] = reduced;
+                    way[c] = col;
+                }
+                if min[c] < delta {
+                    delta = min[c];
+                    next = c;
+                }
+            }
+            for c in 0..=n {
+                if used[c] {
+                    u[matched[c]] += delta;
+                    v[c] -= delta;
+                } else {
+                    min[c] -= delta;
+                }
+            }
+            col = next;
+        }
+        while col != 0 {
+            let prev = way[col];
+            matched[col] = matched[prev];
+            col = prev;
+        }
+    }
+
+    let mut assignment = vec![0; n];
+    for col in 1..=n {
+        assignment[matched[col] - 1] = col - 1;
+    }
+    assignment
+}
+
+impl Compare<(String, types::Type)> for Argument {
+    #[instrument(name = "cmp_arg", skip(self, arg, krate, profile, generics, substs), fields(self_name = ?self.name, self_has_type = %self.ty.as_ref().map(|t| t.to_string()).unwrap_or("<NONE>".to_string()), arg_name = %arg.0, arg_type = %arg.1))]
+    fn compare(
+        &self,
+        arg: &(String, types::Type),
+        krate: &Crate,
+        profile: &ScoringProfile,
+        generics: &mut Generics,
+        substs: &mut HashMap<String, Type>,
+    ) -> Vec<Similarity> {
+        let mut sims = vec![];
+
+        if let Some(ref name) = self.name {
+            sims.append(&mut name.compare(&arg.0, krate, profile, generics, substs));
+            trace!(?sims);
+        }
+
+        if let Some(ref type_) = self.ty {
+            sims.append(&mut type_.compare(&arg.1, krate, profile, generics, substs));
+            trace!(?sims);
+        }
+
+        sims
+    }
+}
+
+impl Compare<Option<types::Type>> for FnRetTy {
+    #[instrument(name = "cmp_ret", skip(self, ret_ty, krate, profile, generics, substs), fields(expected = ?self, actual = ?ret_ty))]
+    fn compare(
+        &self,
+        ret_ty: &Option<types::Type>,
+        krate: &Crate,
+        profile: &ScoringProfile,
+        generics: &mut Generics,
+        substs: &mut HashMap<String, Type>,
+    ) -> Vec<Similarity> {
+        match (self, ret_ty) {
+            (FnRetTy::Return(q), Some(i)) => q.compare(i, krate, profile, generics, substs),
+            (FnRetTy::DefaultReturn, None) => vec![Discrete {
+                kind: Equivalent,
+                reason: SimilarityReason::UnitReturn,
+                category: Category::Return,
+            }],
             _ => vec![Discrete {
                 kind: Different,
-                reason: "return type differs".to_string(),
+                reason: SimilarityReason::ReturnTypeDiffers,
+                category: Category::Return,
             }],
         }
     }
 }
 
-#[instrument(name = "cmp_typ", skip(lhs, rhs, krate, generics, substs), fields(expected = ?lhs, actual = ?rhs))]
+#[instrument(name = "cmp_typ", skip(lhs, rhs, krate, profile, generics, substs), fields(expected = ?lhs, actual = ?rhs))]
 fn compare_type(
     lhs: &Type,
     rhs: &types::Type,
     krate: &Crate,
+    profile: &ScoringProfile,
     generics: &mut Generics,
     substs: &mut HashMap<String, Type>,
-    _allow_recursion: bool,
+    allow_recursion: bool,
 ) -> Vec<Similarity> {
     use {crate::query::Type::*, types::Type};
     tracing::trace!(?lhs, ?rhs, "comparing types");
+    if let Some(sims) = compare_callable(lhs, rhs, krate, profile, generics, substs) {
+        return sims;
+    }
     match (lhs, rhs) {
+        // Bindings of holes share `substs` with candidate generics, `?` keeps them apart.
+        (Hole(name), i) => match resolve_type(i, generics, substs) {
+            Some(i) => match substs.get(&format!("?{}", name)) {
+                Some(bound) if *bound == i => vec![Discrete {
+                    kind: Equivalent,
+                    reason: SimilarityReason::HoleMatchesBinding,
+                    category: Category::Generic,
+                }],
+                Some(_) => vec![Discrete {
+                    kind: Different,
+                    reason: SimilarityReason::HoleBoundInconsistently,
+                    category: Category::Generic,
+                }],
+                None => {
+                    substs.insert(format!("?{}", name), i);
+                    vec![Discrete {
+                        kind: Equivalent,
+                        reason: SimilarityReason::HoleBound,
+                        category: Category::Generic,
+                    }]
+                }
+            },
+            None => vec![Discrete {
+                kind: Subequal,
+                reason: SimilarityReason::HoleMatchesUnrepresentable,
+                category: Category::Generic,
+            }],
+        },
         (q, Type::Generic(i)) if i == "Self" => {
             let mut i = None;
             for where_predicate in &generics.where_predicates {
@@ -362,13 +1634,21 @@ fn compare_type(
             trace!(?i);
 
             match i {
+                None if matches!(q, UnresolvedPath { name, args: None } if name == "Self") => {
+                    vec![Discrete {
+                        kind: Equivalent,
+                        reason: SimilarityReason::SelfMatches,
+                        category: Category::Generic,
+                    }]
+                }
                 None => {
                     vec![Discrete {
                         kind: Subequal,
-                        reason: "unbound Self in where-predicate".to_string(),
+                        reason: SimilarityReason::UnboundSelf,
+                        category: Category::Generic,
                     }]
                 }
-                Some(i) => q.compare(&i, krate, generics, substs),
+                Some(i) => q.compare(&i, krate, profile, generics, substs),
             }
         }
         (q, Type::Generic(i)) => match substs.get(i) {
@@ -376,60 +1656,138 @@ fn compare_type(
                 if q == i {
                     vec![Discrete {
                         kind: Equivalent,
-                        reason: "generic matches substitution".to_string(),
+                        reason: SimilarityReason::GenericMatchesSubstitution,
+                        category: Category::Generic,
                     }]
                 } else {
                     vec![Discrete {
                         kind: Different,
-                        reason: "generic differs from substitution".to_string(),
+                        reason: SimilarityReason::GenericDiffersFromSubstitution,
+                        category: Category::Generic,
                     }]
                 }
             }
             None => {
                 substs.insert(i.clone(), q.clone());
-                vec![Discrete {
-                    kind: Subequal,
-                    reason: "generic substituted".to_string(),
-                }]
+                let unmet = unmet_bounds(q, i, krate, generics);
+                if unmet.is_empty() {
+                    vec![Discrete {
+                        kind: Subequal,
+                        reason: SimilarityReason::GenericSubstituted,
+                        category: Category::Generic,
+                    }]
+                } else {
+                    unmet
+                        .into_iter()
+                        .map(|trait_| Discrete {
+                            kind: Different,
+                            reason: SimilarityReason::UnmetBound {
+                                type_: q.clone(),
+                                trait_,
+                            },
+                            category: Category::Generic,
+                        })
+                        .collect()
+                }
+            }
+        },
+        (
+            QualifiedPath {
+                name: q_name,
+                self_type: q_self,
+                trait_: q_trait,
+            },
+            Type::QualifiedPath {
+                name: i_name,
+                self_type: i_self,
+                trait_: i_trait,
+                ..
+            },
+        ) => {
+            let mut sims = q_name.compare(i_name, krate, profile, generics, substs);
+            sims.append(&mut q_self.compare(i_self.as_ref(), krate, profile, generics, substs));
+            match (q_trait, i_trait) {
+                (Some(q), Some(i)) => sims.append(&mut q.compare(
+                    &Type::ResolvedPath(i.clone()),
+                    krate,
+                    profile,
+                    generics,
+                    substs,
+                )),
+                (Some(_), None) => sims.push(Discrete {
+                    kind: Different,
+                    reason: SimilarityReason::InherentAssociatedType,
+                    category: Category::Other,
+                }),
+                (None, _) => {}
             }
+            sims
+        }
+        (
+            q,
+            Type::QualifiedPath {
+                name,
+                self_type,
+                trait_,
+                ..
+            },
+        ) => match resolve_projection(krate, self_type, trait_.as_ref(), name) {
+            Some(i) => q.compare(i, krate, profile, generics, substs),
+            None => vec![Discrete {
+                kind: Different,
+                reason: SimilarityReason::UnresolvedProjection,
+                category: Category::Other,
+            }],
         },
-        // FIXME: Check what happened to typedefs
-        // (q, Type::ResolvedPath { id, .. })
-        //     if krate
-        //         .index
-        //         .get(id)
-        //         .map(|i| matches!(i.inner, types::ItemEnum::Typedef(_)))
-        //         .unwrap_or(false)
-        //         && allow_recursion =>
-        // {
-        //     let sims_typedef = compare_type(lhs, rhs, krate, generics, substs, false);
-        //     // if let Some(Item {
-        //     //     inner: types::ItemEnum::Typedef(types::Typedef { type_: ref i, .. }),
-        //     //     ..
-        //     // }) = krate.index.get(id)
-        //     // {
-        //     //     // TODO: Acknowledge `generics` of `types::Typedef` to get more accurate search results.
-        //     //     let sims_adt = q.compare(i, krate, generics, substs);
-        //     //     let sum =
-        //     //         |sims: &Vec<Similarity>| -> f32 { sims.iter().map(Similarity::score).sum() };
-        //     //     if sum(&sims_adt) < sum(&sims_typedef) {
-        //     //         return sims_adt;
-        //     //     }
-        //     // }
-        //     sims_typedef
-        // }
+        // An alias is compared both as written and as the type it stands for, keeping whichever
+        // is closer. Aliases of aliases are expanded at once, so neither comparison expands again.
+        (q, Type::ResolvedPath(path)) if allow_recursion => {
+            let Some(expanded) = expand_alias(path, krate) else {
+                return compare_type(q, rhs, krate, profile, generics, substs, false);
+            };
+            let mut expanded_generics = generics.clone();
+            let mut expanded_substs = substs.clone();
+            let (aliased, aliased_explained) =
+                explain::detached(|| compare_type(q, rhs, krate, profile, generics, substs, false));
+            let (expanded, expanded_explained) = explain::detached(|| {
+                compare_type(
+                    q,
+                    &expanded,
+                    krate,
+                    profile,
+                    &mut expanded_generics,
+                    &mut expanded_substs,
+                    false,
+                )
+            });
+            trace!(?aliased, ?expanded);
+
+            if closer(&expanded, &aliased, profile) {
+                *generics = expanded_generics;
+                *substs = expanded_substs;
+                explain::attach(expanded_explained);
+                expanded
+            } else {
+                explain::attach(aliased_explained);
+                aliased
+            }
+        }
         (Tuple(q), Type::Tuple(i)) => {
             let mut sims = q
                 .iter()
                 .zip(i.iter())
-                .filter_map(|(q, i)| q.as_ref().map(|q| q.compare(i, krate, generics, substs)))
+                .filter_map(|(q, i)| {
+                    q.as_ref()
+                        .map(|q| q.compare(i, krate, profile, generics, substs))
+                })
                 .flatten()
                 .collect::<Vec<_>>();
 
             // They are both tuples.
             sims.push(Discrete {
                 kind: Equivalent,
-                reason: "tuple shape".to_string(),
+                reason: SimilarityReason::TupleShape,
+                category: Category::Other,
             });
 
             // FIXME: Replace this line below with `usize::abs_diff` once it got stablized.
@@ -437,22 +1795,65 @@ fn compare_type(
             sims.append(&mut vec![
                 Discrete {
                     kind: Different,
-                    reason: "tuple length differs".to_string()
+                    reason: SimilarityReason::TupleLengthDiffers,
+                    category: Category::Arity,
                 };
                 abs_diff
             ]);
 
             sims
         }
+        (
+            Array {
+                type_: q,
+                len: q_len,
+            },
+            Type::Array {
+                type_: i,
+                len: i_len,
+            },
+        ) => {
+            // They are both arrays.
+            let mut sims = vec![Discrete {
+                kind: Equivalent,
+                reason: SimilarityReason::ArrayType,
+                category: Category::Other,
+            }];
+
+            if let Some(q) = q {
+                sims.append(&mut q.compare(i.as_ref(), krate, profile, generics, substs));
+            }
+            if let Some(q_len) = q_len {
+                sims.append(&mut compare_const(
+                    q_len, i_len, krate, profile, generics, substs,
+                ));
+            }
+
+            sims
+        }
+        (Slice(q), Type::Array { type_: i, .. }) | (Array { type_: q, .. }, Type::Slice(i)) => {
+            let mut sims = vec![Discrete {
+                kind: Subequal,
+                reason: SimilarityReason::SliceAndArray,
+                category: Category::Wrapper,
+            }];
+
+            if let Some(q) = q {
+                sims.append(&mut q.compare(i.as_ref(), krate, profile, generics, substs));
+            }
+
+            sims
+        }
         (Slice(q), Type::Slice(i)) => {
             // They are both slices.
             let mut sims = vec![Discrete {
                 kind: Equivalent,
-                reason: "slice type".to_string(),
+                reason: SimilarityReason::SliceType,
+                category: Category::Other,
             }];
 
             if let Some(q) = q {
-                sims.append(&mut q.compare(i.as_ref(), krate, generics, substs));
+                sims.append(&mut q.compare(i.as_ref(), krate, profile, generics, substs));
             }
 
             sims
@@ -479,29 +1880,32 @@ fn compare_type(
             },
         ) => {
             if q_mut == i_mut {
-                q.compare(i.as_ref(), krate, generics, substs)
+                q.compare(i.as_ref(), krate, profile, generics, substs)
             } else {
-                let mut sims = q.compare(i.as_ref(), krate, generics, substs);
+                let mut sims = q.compare(i.as_ref(), krate, profile, generics, substs);
                 sims.push(Discrete {
                     kind: Subequal,
-                    reason: "mutability differs".to_string(),
+                    reason: SimilarityReason::MutabilityDiffers,
+                    category: Category::Wrapper,
                 });
                 sims
             }
         }
         (q, Type::RawPointer { type_: i, .. } | Type::BorrowedRef { type_: i, .. }) => {
-            let mut sims = q.compare(i.as_ref(), krate, generics, substs);
+            let mut sims = q.compare(i.as_ref(), krate, profile, generics, substs);
             sims.push(Discrete {
                 kind: Subequal,
-                reason: "pointer/reference wrapper".to_string(),
+                reason: SimilarityReason::PointerWrapper,
+                category: Category::Wrapper,
             });
             sims
         }
         (RawPointer { type_: q, .. } | BorrowedRef { type_: q, .. }, i) => {
-            let mut sims = q.compare(i, krate, generics, substs);
+            let mut sims = q.compare(i, krate, profile, generics, substs);
             sims.push(Discrete {
                 kind: Subequal,
-                reason: "pointer/reference wrapper".to_string(),
+                reason: SimilarityReason::PointerWrapper,
+                category: Category::Wrapper,
             });
             sims
         }
@@ -510,77 +1914,1123 @@ fn compare_type(
                 name: q,
                 args: q_args,
             },
-            Type::ResolvedPath(types::Path {
-                path: i,
-                args: i_args,
-                ..
-            }),
+            Type::ResolvedPath(
+                path @ types::Path {
+                    path: i,
+                    args: i_args,
+                    ..
+                },
+            ),
         ) => {
-            let mut sims = q.compare(i, krate, generics, substs);
+            let mut sims = vec![];
+            match compare_qualifier(q, resolved_path(path, krate).as_deref()) {
+                Some(
+                    sim @ Discrete {
+                        kind: Different, ..
+                    },
+                ) => return vec![sim],
+                Some(sim) => sims.push(sim),
+                None => {}
+            }
+            // A type name is part of the type, not an item name.
+            let mut names = q.compare(i, krate, profile, generics, substs);
+            names
+                .iter_mut()
+                .for_each(|sim| sim.set_category(Category::Other));
+            sims.append(&mut names);
+            sims.append(&mut compare_generic_args(
+                q_args, i_args, krate, profile, generics, substs,
+            ));
+            sims
+        }
+        (Primitive(q), Type::Primitive(i)) => q.compare(i, krate, profile, generics, substs),
+        (ImplTrait(q), Type::ImplTrait(i)) => {
+            compare_trait_bounds(q, i, krate, profile, generics, substs)
+        }
+        (DynTrait(q), Type::DynTrait(i)) => {
+            compare_trait_bounds(q, &dyn_bounds(i), krate, profile, generics, substs)
+        }
+        // Both erase the concrete type behind the same traits.
+        (ImplTrait(q), Type::DynTrait(i)) => {
+            let mut sims =
+                compare_trait_bounds(q, &dyn_bounds(i), krate, profile, generics, substs);
+            sims.push(Discrete {
+                kind: Subequal,
+                reason: SimilarityReason::OpaqueTypeAndTraitObject,
+                category: Category::Wrapper,
+            });
+            sims
+        }
+        (DynTrait(q), Type::ImplTrait(i)) => {
+            let mut sims = compare_trait_bounds(q, i, krate, profile, generics, substs);
+            sims.push(Discrete {
+                kind: Subequal,
+                reason: SimilarityReason::OpaqueTypeAndTraitObject,
+                category: Category::Wrapper,
+            });
+            sims
+        }
+        (q, i) => vec![Discrete {
+            kind: Different,
+            reason: SimilarityReason::TypeMismatch {
+                query: Box::new(q.clone()),
+                candidate: Box::new(i.clone()),
+            },
+            category: Category::Other,
+        }],
+    }
+}
 
-            match (q_args, i_args) {
-                #[allow(clippy::single_match)]
-                (Some(q), Some(i)) => match (&**q, &**i) {
-                    (
-                        GenericArgs::AngleBracketed { args: ref q },
-                        types::GenericArgs::AngleBracketed { args: ref i, .. },
-                    ) => {
-                        let q = q.iter().map(|q| {
-                            q.as_ref().map(|q| match q {
-                                GenericArg::Type(q) => q,
-                            })
-                        });
-                        let i = i.iter().map(|i| match i {
-                            types::GenericArg::Type(t) => Some(t),
-                            _ => None,
-                        });
-                        q.zip(i).for_each(|(q, i)| match (q, i) {
-                            (Some(q), Some(i)) => {
-                                sims.append(&mut q.compare(i, krate, generics, substs))
+/// Finds the type `<self_type as trait_>::name` stands for, from the impls of `self_type` in
+/// `krate`.
+fn resolve_projection<'a>(
+    krate: &'a Crate,
+    self_type: &types::Type,
+    trait_: Option<&types::Path>,
+    name: &str,
+) -> Option<&'a types::Type> {
+    let types::Type::ResolvedPath(path) = self_type else {
+        return None;
+    };
+    let impls = match krate.index.get(&path.id)?.inner {
+        types::ItemEnum::Struct(ref s) => &s.impls,
+        types::ItemEnum::Enum(ref e) => &e.impls,
+        types::ItemEnum::Union(ref u) => &u.impls,
+        _ => return None,
+    };
+
+    impls
+        .iter()
+        .filter_map(|id| match krate.index.get(id)?.inner {
+            types::ItemEnum::Impl(ref impl_) => Some(impl_),
+            _ => None,
+        })
+        .filter(|impl_| match (trait_, &impl_.trait_) {
+            (Some(trait_), Some(impl_trait)) => trait_.id == impl_trait.id,
+            (Some(_), None) => false,
+            (None, _) => true,
+        })
+        .flat_map(|impl_| impl_.items.iter())
+        .find_map(|id| match krate.index.get(id)? {
+            types::Item {
+                name: Some(item_name),
+                inner:
+                    types::ItemEnum::AssocType {
+                        type_: Some(ref type_),
+                        ..
+                    },
+                ..
+            } if item_name == name => Some(type_),
+            _ => None,
+        })
+}
+
+/// Implementors of common std traits among primitives and std types, for bounds on traits
+/// that are not in the searched crate, keyed by the path of the trait within `std`. Primitive
+/// implementors are listed exhaustively, other types only when they are known to implement it.
+const STD_TRAIT_IMPLS: [(&str, &[&str]); 9] = [
+    (
+        "io::Read",
+        &[
+            "File",
+            "TcpStream",
+            "UnixStream",
+            "Stdin",
+            "StdinLock",
+            "ChildStdout",
+            "ChildStderr",
+            "Cursor",
+            "BufReader",
+            "Chain",
+            "Take",
+            "Empty",
+            "Repeat",
+            "VecDeque",
+        ],
+    ),
+    (
+        "io::Write",
+        &[
+            "File",
+            "TcpStream",
+            "UnixStream",
+            "Stdout",
+            "StdoutLock",
+            "Stderr",
+            "StderrLock",
+            "ChildStdin",
+            "Cursor",
+            "BufWriter",
+            "LineWriter",
+            "Vec",
+            "VecDeque",
+            "Sink",
+            "Empty",
+        ],
+    ),
+    ("fmt::Write", &["String"]),
+    (
+        "io::BufRead",
+        &["BufReader", "Cursor", "StdinLock", "Chain", "Take", "Empty"],
+    ),
+    (
+        "marker::Copy",
+        &[
+            "i8", "i16", "i32", "i64", "i128", "isize", "u8", "u16", "u32", "u64", "u128", "usize",
+            "f32", "f64", "bool", "char",
+        ],
+    ),
+    (
+        "cmp::Eq",
+        &[
+            "i8", "i16", "i32", "i64", "i128", "isize", "u8", "u16", "u32", "u64", "u128", "usize",
+            "bool", "char", "str", "String", "Vec", "VecDeque", "Cursor",
+        ],
+    ),
+    (
+        "cmp::Ord",
+        &[
+            "i8", "i16", "i32", "i64", "i128", "isize", "u8", "u16", "u32", "u64", "u128", "usize",
+            "bool", "char", "str", "String", "Vec", "VecDeque",
+        ],
+    ),
+    (
+        "hash::Hash",
+        &[
+            "i8", "i16", "i32", "i64", "i128", "isize", "u8", "u16", "u32", "u64", "u128", "usize",
+            "bool", "char", "str", "String", "Vec", "VecDeque",
+        ],
+    ),
+    (
+        "fmt::Display",
+        &[
+            "i8", "i16", "i32", "i64", "i128", "isize", "u8", "u16", "u32", "u64", "u128", "usize",
+            "f32", "f64", "bool", "char", "str", "String",
+        ],
+    ),
+];
+
+/// The traits bounding the candidate generic `name` that `q` is known not to implement.
+fn unmet_bounds(q: &Type, name: &str, krate: &Crate, generics: &Generics) -> Vec<String> {
+    let type_ = match q {
+        Type::UnresolvedPath { name, .. } => name.as_str(),
+        Type::Primitive(prim) => prim.as_str(),
+        _ => return vec![],
+    };
+    let params = generics
+        .params
+        .iter()
+        .filter(|param| param.name == name)
+        .flat_map(|param| match &param.kind {
+            types::GenericParamDefKind::Type { bounds, .. } => bounds.as_slice(),
+            _ => &[],
+        });
+    let predicates = generics
+        .where_predicates
+        .iter()
+        .flat_map(|predicate| match predicate {
+            types::WherePredicate::BoundPredicate {
+                type_: types::Type::Generic(generic),
+                bounds,
+                ..
+            } if generic == name => bounds.as_slice(),
+            _ => &[],
+        });
+    params
+        .chain(predicates)
+        .filter_map(|bound| match bound {
+            types::GenericBound::TraitBound {
+                trait_,
+                modifier: types::TraitBoundModifier::None,
+                ..
+            } => Some(trait_),
+            _ => None,
+        })
+        .filter(|trait_| implements(type_, trait_, krate) == Some(false))
+        .map(|trait_| trait_.path.clone())
+        .collect()
+}
+
+/// Whether the type named `type_`, possibly path-qualified, implements `trait_`, judging by the
+/// impls of `trait_` in `krate` or [`STD_TRAIT_IMPLS`], or `None` if it can't be told.
+fn implements(type_: &str, trait_: &types::Path, krate: &Crate) -> Option<bool> {
+    let segments = type_.split("::").collect::<Vec<_>>();
+    let names_type = |path: &[String]| {
+        path.len() >= segments.len()
+            && path[path.len() - segments.len()..]
+                .iter()
+                .zip(&segments)
+                .all(|(a, b)| a == b)
+    };
+    let primitive = PrimitiveType::from_name(type_).is_some();
+
+    if let Some(types::ItemEnum::Trait(t)) = krate.index.get(&trait_.id).map(|item| &item.inner) {
+        let mut unknown = false;
+        for impl_ in t
+            .implementations
+            .iter()
+            .filter_map(|id| match krate.index.get(id)?.inner {
+                types::ItemEnum::Impl(ref impl_) => Some(impl_),
+                _ => None,
+            })
+        {
+            let implemented = match &impl_.for_ {
+                _ if impl_.blanket_impl.is_some() => None,
+                types::Type::ResolvedPath(path) => Some(names_type(
+                    &resolved_path(path, krate).unwrap_or_else(|| vec![path.path.clone()]),
+                )),
+                types::Type::Primitive(prim) => Some(prim == type_),
+                _ => None,
+            };
+            match implemented {
+                Some(true) => return Some(true),
+                Some(false) => {}
+                // Blanket impls and impls for references may cover any type.
+                None => unknown = true,
+            }
+        }
+        // Types of downstream crates, unknown to `krate`, may implement its traits too.
+        let known = primitive
+            || krate.paths.values().any(|summary| {
+                matches!(
+                    summary.kind,
+                    types::ItemKind::Struct | types::ItemKind::Enum | types::ItemKind::Union
+                ) && names_type(&summary.path)
+            });
+        return (!unknown && known).then_some(false);
+    }
+
+    let path = resolved_path(trait_, krate)?;
+    let (krate_name, path) = path.split_first()?;
+    if !matches!(krate_name.as_str(), "std" | "core" | "alloc") {
+        return None;
+    }
+    let path = path.join("::");
+    let (_, implementors) = STD_TRAIT_IMPLS.iter().find(|(name, _)| *name == path)?;
+    if implementors.contains(segments.last()?) {
+        Some(true)
+    } else if primitive {
+        Some(false)
+    } else {
+        None
+    }
+}
+
+/// Aliases of aliases followed by [`expand_alias`] before giving up.
+const MAX_ALIAS_DEPTH: usize = 8;
+
+/// The type `path` stands for when it names a type alias of `krate`, with the generics of the
+/// alias substituted by the arguments of `path`.
+fn expand_alias(path: &types::Path, krate: &Crate) -> Option<types::Type> {
+    let mut expanded: Option<types::Type> = None;
+    for _ in 0..MAX_ALIAS_DEPTH {
+        let path = match &expanded {
+            None => path,
+            Some(types::Type::ResolvedPath(path)) => path,
+            Some(_) => break,
+        };
+        let Some(types::ItemEnum::TypeAlias(alias)) = krate.index.get(&path.id).map(|i| &i.inner)
+        else {
+            break;
+        };
+
+        let args = match path.args.as_deref() {
+            Some(types::GenericArgs::AngleBracketed { args, .. }) => args
+                .iter()
+                .filter_map(|arg| match arg {
+                    types::GenericArg::Type(ty) => Some(ty),
+                    _ => None,
+                })
+                .collect(),
+            _ => vec![],
+        };
+        // Parameters left out, as `E` in `Result<T>` for `type Result<T, E = Error>`, take
+        // their defaults.
+        let params = alias
+            .generics
+            .params
+            .iter()
+            .filter_map(|param| match &param.kind {
+                types::GenericParamDefKind::Type { default, .. } => {
+                    Some((param.name.as_str(), default.as_ref()))
+                }
+                _ => None,
+            })
+            .enumerate()
+            .filter_map(|(idx, (name, default))| {
+                args.get(idx).copied().or(default).map(|ty| (name, ty))
+            })
+            .collect::<HashMap<_, _>>();
+
+        let next = substitute_generics(&alias.type_, &params);
+        expanded = Some(next);
+    }
+    expanded
+}
+
+/// Replaces the generics of `type_` named in `params` by the types they map to.
+fn substitute_generics(type_: &types::Type, params: &HashMap<&str, &types::Type>) -> types::Type {
+    let subst = |ty: &types::Type| substitute_generics(ty, params);
+    let subst_args = |args: &types::GenericArgs| match args {
+        types::GenericArgs::AngleBracketed { args, constraints } => {
+            types::GenericArgs::AngleBracketed {
+                args: args
+                    .iter()
+                    .map(|arg| match arg {
+                        types::GenericArg::Type(ty) => types::GenericArg::Type(subst(ty)),
+                        arg => arg.clone(),
+                    })
+                    .collect(),
+                constraints: constraints
+                    .iter()
+                    .map(|constraint| types::AssocItemConstraint {
+                        binding: match &constraint.binding {
+                            types::AssocItemConstraintKind::Equality(types::Term::Type(ty)) => {
+                                types::AssocItemConstraintKind::Equality(types::Term::Type(subst(
+                                    ty,
+                                )))
                             }
-                            (Some(_), None) => sims.push(Discrete {
-                                kind: Different,
-                                reason: "missing generic arg".to_string(),
-                            }),
-                            (None, _) => {}
-                        });
+                            binding => binding.clone(),
+                        },
+                        ..constraint.clone()
+                    })
+                    .collect(),
+            }
+        }
+        types::GenericArgs::Parenthesized { inputs, output } => types::GenericArgs::Parenthesized {
+            inputs: inputs.iter().map(subst).collect(),
+            output: output.as_ref().map(subst),
+        },
+        args => args.clone(),
+    };
+
+    match type_ {
+        types::Type::Generic(name) => params
+            .get(name.as_str())
+            .map_or_else(|| type_.clone(), |ty| (*ty).clone()),
+        types::Type::ResolvedPath(path) => types::Type::ResolvedPath(types::Path {
+            args: path.args.as_deref().map(|args| Box::new(subst_args(args))),
+            ..path.clone()
+        }),
+        types::Type::Tuple(types_) => types::Type::Tuple(types_.iter().map(subst).collect()),
+        types::Type::Slice(ty) => types::Type::Slice(Box::new(subst(ty))),
+        types::Type::Array { type_, len } => types::Type::Array {
+            type_: Box::new(subst(type_)),
+            len: len.clone(),
+        },
+        types::Type::RawPointer { is_mutable, type_ } => types::Type::RawPointer {
+            is_mutable: *is_mutable,
+            type_: Box::new(subst(type_)),
+        },
+        types::Type::BorrowedRef {
+            lifetime,
+            is_mutable,
+            type_,
+        } => types::Type::BorrowedRef {
+            lifetime: lifetime.clone(),
+            is_mutable: *is_mutable,
+            type_: Box::new(subst(type_)),
+        },
+        types::Type::QualifiedPath {
+            name,
+            args,
+            self_type,
+            trait_,
+        } => types::Type::QualifiedPath {
+            name: name.clone(),
+            args: args.as_deref().map(|args| Box::new(subst_args(args))),
+            self_type: Box::new(subst(self_type)),
+            trait_: trait_.clone(),
+        },
+        _ => type_.clone(),
+    }
+}
+
+/// The `Fn*` traits, which take parenthesized arguments.
+const FN_TRAITS: [&str; 3] = ["Fn", "FnMut", "FnOnce"];
+
+/// Something that can be called: a closure bounded by one of [`FN_TRAITS`], or a function
+/// pointer when `trait_` is `None`.
+struct Callable<T> {
+    trait_: Option<String>,
+    /// `None` stands for `..`, any arguments.
+    inputs: Option<Vec<Option<T>>>,
+    /// `None` stands for any output.
+    output: Option<T>,
+}
+
+impl Callable<Type> {
+    fn from_query(q: &Type) -> Option<Self> {
+        match q {
+            Type::FunctionPointer(decl) => Some(Callable {
+                trait_: None,
+                inputs: decl
+                    .inputs
+                    .as_ref()
+                    .map(|args| args.iter().map(|arg| arg.ty.clone()).collect()),
+                output: match decl.output {
+                    Some(FnRetTy::Return(ref ty)) => Some(ty.clone()),
+                    Some(FnRetTy::DefaultReturn) => Some(Type::Tuple(vec![])),
+                    None => None,
+                },
+            }),
+            Type::UnresolvedPath { name, args } if FN_TRAITS.contains(&name.as_str()) => {
+                let (inputs, output) = match args.as_deref() {
+                    Some(GenericArgs::Parenthesized { inputs, output }) => (
+                        Some(inputs.iter().cloned().map(Some).collect()),
+                        output.clone(),
+                    ),
+                    _ => (None, None),
+                };
+                Some(Callable {
+                    trait_: Some(name.clone()),
+                    inputs,
+                    output,
+                })
+            }
+            _ => None,
+        }
+    }
+}
+
+impl Callable<types::Type> {
+    fn from_bound(bound: &types::GenericBound) -> Option<Self> {
+        let types::GenericBound::TraitBound { trait_, .. } = bound else {
+            return None;
+        };
+        let name = trait_.path.split("::").last()?;
+        if !FN_TRAITS.contains(&name) {
+            return None;
+        }
+        let (inputs, output) = match trait_.args.as_deref() {
+            Some(types::GenericArgs::Parenthesized { inputs, output }) => (
+                Some(inputs.iter().cloned().map(Some).collect()),
+                Some(output.clone().unwrap_or(types::Type::Tuple(vec![]))),
+            ),
+            _ => (None, None),
+        };
+        Some(Callable {
+            trait_: Some(name.to_owned()),
+            inputs,
+            output,
+        })
+    }
+
+    /// Every way `i` can be called, looking through the bounds of generic parameters.
+    fn from_candidate(i: &types::Type, generics: &Generics) -> Vec<Self> {
+        match i {
+            types::Type::FunctionPointer(fp) => vec![Callable {
+                trait_: None,
+                inputs: Some(fp.sig.inputs.iter().map(|(_, t)| Some(t.clone())).collect()),
+                output: Some(fp.sig.output.clone().unwrap_or(types::Type::Tuple(vec![]))),
+            }],
+            types::Type::ImplTrait(bounds) => bounds.iter().filter_map(Self::from_bound).collect(),
+            types::Type::DynTrait(dyn_) => dyn_bounds(dyn_)
+                .iter()
+                .filter_map(Self::from_bound)
+                .collect(),
+            types::Type::Generic(name) => {
+                let params = generics.params.iter().filter_map(|p| match p.kind {
+                    types::GenericParamDefKind::Type { ref bounds, .. } if &p.name == name => {
+                        Some(bounds)
                     }
-                    // TODO: Support `GenericArgs::Parenthesized`.
-                    (_, _) => {}
+                    _ => None,
+                });
+                let predicates = generics.where_predicates.iter().filter_map(|p| match p {
+                    types::WherePredicate::BoundPredicate {
+                        type_: types::Type::Generic(ty),
+                        bounds,
+                        ..
+                    } if ty == name => Some(bounds),
+                    _ => None,
+                });
+                params
+                    .chain(predicates)
+                    .flatten()
+                    .filter_map(Self::from_bound)
+                    .collect()
+            }
+            _ => vec![],
+        }
+    }
+}
+
+/// Compares closures and function pointers by their signature, picking the closest way the
+/// candidate can be called. Returns `None` unless both sides are callable.
+fn compare_callable(
+    lhs: &Type,
+    rhs: &types::Type,
+    krate: &Crate,
+    profile: &ScoringProfile,
+    generics: &mut Generics,
+    substs: &mut HashMap<String, Type>,
+) -> Option<Vec<Similarity>> {
+    let q = Callable::from_query(lhs)?;
+
+    let sims = Callable::from_candidate(rhs, generics)
+        .iter()
+        .map(|i| {
+            let mut substs_ = substs.clone();
+            let (mut sims, explained) = explain::detached(|| {
+                compare_callable_signature(&q, i, krate, profile, generics, &mut substs_)
+            });
+            sims.push(match (&q.trait_, &i.trait_) {
+                (Some(q), Some(i)) if q == i => Discrete {
+                    kind: Equivalent,
+                    reason: SimilarityReason::ClosureTraitMatches,
+                    category: Category::Other,
                 },
-                (Some(q), None) => {
-                    let GenericArgs::AngleBracketed { args: ref q } = **q;
-                    sims.append(&mut vec![
-                        Discrete {
-                            kind: Different,
-                            reason: "missing generic args".to_string()
-                        };
-                        q.len()
-                    ])
-                }
-                (None, _) => {}
+                (None, None) => Discrete {
+                    kind: Equivalent,
+                    reason: SimilarityReason::FunctionPointer,
+                    category: Category::Other,
+                },
+                (Some(_), Some(_)) => Discrete {
+                    kind: Subequal,
+                    reason: SimilarityReason::ClosureTraitDiffers,
+                    category: Category::Other,
+                },
+                _ => Discrete {
+                    kind: Subequal,
+                    reason: SimilarityReason::FunctionPointerAndClosure,
+                    category: Category::Wrapper,
+                },
+            });
+            (sims, substs_, explained)
+        })
+        .reduce(|best, next| {
+            if closer(&next.0, &best.0, profile) {
+                next
+            } else {
+                best
             }
+        })?;
 
-            sims
+    let (sims, substs_, explained) = sims;
+    *substs = substs_;
+    explain::attach(explained);
+    if let types::Type::Generic(name) = rhs {
+        substs.insert(name.clone(), lhs.clone());
+    }
+    Some(sims)
+}
+
+fn compare_callable_signature(
+    q: &Callable<Type>,
+    i: &Callable<types::Type>,
+    krate: &Crate,
+    profile: &ScoringProfile,
+    generics: &mut Generics,
+    substs: &mut HashMap<String, Type>,
+) -> Vec<Similarity> {
+    let mut sims = vec![];
+
+    if let (Some(q_inputs), Some(i_inputs)) = (&q.inputs, &i.inputs) {
+        for (q, i) in q_inputs.iter().zip(i_inputs.iter()) {
+            if let (Some(q), Some(i)) = (q, i) {
+                sims.append(&mut q.compare(i, krate, profile, generics, substs));
+            }
         }
-        (Primitive(q), Type::Primitive(i)) => q.compare(i, krate, generics, substs),
-        _ => vec![Discrete {
+        let abs_diff = max(q_inputs.len(), i_inputs.len()) - min(q_inputs.len(), i_inputs.len());
+        sims.append(&mut vec![
+            Discrete {
+                kind: Different,
+                reason: SimilarityReason::ArgumentCountDiffers {
+                    query: q_inputs.len(),
+                    candidate: i_inputs.len(),
+                },
+                category: Category::Arity,
+            };
+            abs_diff
+        ]);
+    }
+    if let (Some(q), Some(i)) = (&q.output, &i.output) {
+        sims.append(&mut q.compare(i, krate, profile, generics, substs));
+    }
+
+    sims
+}
+
+fn compare_trait_bounds(
+    q: &[GenericBound],
+    i: &Vec<types::GenericBound>,
+    krate: &Crate,
+    profile: &ScoringProfile,
+    generics: &mut Generics,
+    substs: &mut HashMap<String, Type>,
+) -> Vec<Similarity> {
+    q.iter()
+        .flat_map(|bound| bound.compare(i, krate, profile, generics, substs))
+        .collect()
+}
+
+/// The traits of a trait object as bounds, so they compare like the ones of `impl Trait`.
+fn dyn_bounds(dyn_: &types::DynTrait) -> Vec<types::GenericBound> {
+    dyn_.traits
+        .iter()
+        .map(|poly| types::GenericBound::TraitBound {
+            trait_: poly.trait_.clone(),
+            generic_params: poly.generic_params.clone(),
+            modifier: types::TraitBoundModifier::None,
+        })
+        .collect()
+}
+
+/// Compares a const generic argument or array length, e.g. `32` or `N`. Const generic
+/// parameters of the candidate unify with the query like type generics do.
+fn compare_const(
+    q: &str,
+    i: &str,
+    krate: &Crate,
+    profile: &ScoringProfile,
+    generics: &mut Generics,
+    substs: &mut HashMap<String, Type>,
+) -> Vec<Similarity> {
+    let is_param = generics
+        .params
+        .iter()
+        .any(|p| p.name == i && matches!(p.kind, types::GenericParamDefKind::Const { .. }));
+    let is_ident = q.starts_with(|c: char| c.is_alphabetic() || c == '_');
+
+    match (is_param, is_ident) {
+        (true, true) => compare_type(
+            &Type::Generic(q.to_owned()),
+            &types::Type::Generic(i.to_owned()),
+            krate,
+            profile,
+            generics,
+            substs,
+            true,
+        ),
+        (true, false) => vec![Discrete {
+            kind: Subequal,
+            reason: SimilarityReason::ConstGenericSubstituted,
+            category: Category::Generic,
+        }],
+        (false, _) if q == i => vec![Discrete {
+            kind: Equivalent,
+            reason: SimilarityReason::ConstMatches,
+            category: Category::Generic,
+        }],
+        (false, _) => vec![Discrete {
             kind: Different,
-            reason: "type mismatch".to_string(),
+            reason: SimilarityReason::ConstDiffers,
+            category: Category::Generic,
         }],
     }
 }
 
+fn compare_generic_args(
+    q_args: &Option<Box<GenericArgs>>,
+    i_args: &Option<Box<types::GenericArgs>>,
+    krate: &Crate,
+    profile: &ScoringProfile,
+    generics: &mut Generics,
+    substs: &mut HashMap<String, Type>,
+) -> Vec<Similarity> {
+    let mut sims = vec![];
+
+    match (q_args, i_args) {
+        #[allow(clippy::single_match)]
+        (Some(q), Some(i)) => match (&**q, &**i) {
+            (
+                GenericArgs::AngleBracketed {
+                    args: ref q,
+                    constraints: ref q_constraints,
+                },
+                types::GenericArgs::AngleBracketed {
+                    args: ref i,
+                    constraints: ref i_constraints,
+                },
+            ) => {
+                for q in q_constraints {
+                    sims.append(&mut explain::record(
+                        profile,
+                        || format!("constraint `{}`", q.name),
+                        || q.compare(i_constraints, krate, profile, generics, substs),
+                    ));
+                }
+
+                // Lifetimes are not compared, so they are left out before lining args up.
+                let q = q
+                    .iter()
+                    .filter(|q| !matches!(q, Some(GenericArg::Lifetime(_))));
+                let i = i
+                    .iter()
+                    .filter(|i| !matches!(i, types::GenericArg::Lifetime(_)));
+                // Args the candidate doesn't have, as `E` in `Result<T, E>` for an alias
+                // `Result<T>`, are missing.
+                let excess = q.clone().skip(i.clone().count()).flatten().count();
+                sims.append(&mut vec![
+                    Discrete {
+                        kind: Different,
+                        reason: SimilarityReason::MissingGenericArg,
+                        category: Category::Generic,
+                    };
+                    excess
+                ]);
+                q.zip(i).enumerate().for_each(|(k, (q, i))| match (q, i) {
+                    (Some(GenericArg::Type(q)), types::GenericArg::Type(i)) => {
+                        sims.append(&mut explain::record(
+                            profile,
+                            || format!("generic arg {}", k),
+                            || q.compare(i, krate, profile, generics, substs),
+                        ))
+                    }
+                    (
+                        Some(GenericArg::Const(q) | GenericArg::Type(Type::Generic(q))),
+                        types::GenericArg::Const(i),
+                    ) => sims.append(&mut compare_const(
+                        q, &i.expr, krate, profile, generics, substs,
+                    )),
+                    (Some(_), _) => sims.push(Discrete {
+                        kind: Different,
+                        reason: SimilarityReason::MissingGenericArg,
+                        category: Category::Generic,
+                    }),
+                    (None, _) => {}
+                });
+            }
+            (
+                GenericArgs::Parenthesized {
+                    inputs: q_inputs,
+                    output: q_output,
+                },
+                types::GenericArgs::Parenthesized {
+                    inputs: i_inputs,
+                    output: i_output,
+                },
+            ) => {
+                let q = Callable {
+                    trait_: None,
+                    inputs: Some(q_inputs.iter().cloned().map(Some).collect()),
+                    output: q_output.clone(),
+                };
+                let i = Callable {
+                    trait_: None,
+                    inputs: Some(i_inputs.iter().cloned().map(Some).collect()),
+                    output: Some(i_output.clone().unwrap_or(types::Type::Tuple(vec![]))),
+                };
+                sims.append(&mut compare_callable_signature(
+                    &q, &i, krate, profile, generics, substs,
+                ));
+            }
+            (_, _) => sims.push(Discrete {
+                kind: Different,
+                reason: SimilarityReason::GenericArgsShapeDiffers,
+                category: Category::Generic,
+            }),
+        },
+        (Some(q), None) => {
+            let count = match **q {
+                GenericArgs::AngleBracketed {
+                    ref args,
+                    ref constraints,
+                } => {
+                    args.iter()
+                        .filter(|arg| !matches!(arg, Some(GenericArg::Lifetime(_))))
+                        .count()
+                        + constraints.len()
+                }
+                GenericArgs::Parenthesized {
+                    ref inputs,
+                    ref output,
+                } => inputs.len() + usize::from(output.is_some()),
+            };
+            sims.append(&mut vec![
+                Discrete {
+                    kind: Different,
+                    reason: SimilarityReason::MissingGenericArgs,
+                    category: Category::Generic,
+                };
+                count
+            ])
+        }
+        (None, _) => {}
+    }
+
+    sims
+}
+
+impl Compare<Vec<types::AssocItemConstraint>> for AssocItemConstraint {
+    #[instrument(name = "cmp_constraint", skip(self, constraints, krate, profile, generics, substs), fields(name = %self.name, term = %self.term))]
+    fn compare(
+        &self,
+        constraints: &Vec<types::AssocItemConstraint>,
+        krate: &Crate,
+        profile: &ScoringProfile,
+        generics: &mut Generics,
+        substs: &mut HashMap<String, Type>,
+    ) -> Vec<Similarity> {
+        let binding = constraints
+            .iter()
+            .find(|c| c.name == self.name)
+            .map(|c| &c.binding);
+
+        match binding {
+            Some(types::AssocItemConstraintKind::Equality(types::Term::Type(i))) => {
+                self.term.compare(i, krate, profile, generics, substs)
+            }
+            Some(_) => vec![Discrete {
+                kind: Subequal,
+                reason: SimilarityReason::AssocConstraintDiffers,
+                category: Category::Generic,
+            }],
+            None => vec![Discrete {
+                kind: Different,
+                reason: SimilarityReason::MissingAssocConstraint,
+                category: Category::Generic,
+            }],
+        }
+    }
+}
+
 impl Compare<types::Type> for Type {
     fn compare(
         &self,
         type_: &types::Type,
         krate: &Crate,
+        profile: &ScoringProfile,
         generics: &mut Generics,
         substs: &mut HashMap<String, Type>,
     ) -> Vec<Similarity> {
-        compare_type(self, type_, krate, generics, substs, true)
+        explain::record(
+            profile,
+            || format!("`{}` ~ `{}`", self, type_),
+            || compare_coercible(self, type_, krate, profile, generics, substs),
+        )
+    }
+}
+
+/// Compares `q` and `i` as they are and as the types they coerce to, keeping the closest.
+fn compare_coercible(
+    q: &Type,
+    i: &types::Type,
+    krate: &Crate,
+    profile: &ScoringProfile,
+    generics: &mut Generics,
+    substs: &mut HashMap<String, Type>,
+) -> Vec<Similarity> {
+    let coercions = coercions(q, i, krate);
+    if coercions.is_empty() {
+        return compare_type(q, i, krate, profile, generics, substs, true);
     }
+
+    // A coercion is only worth it when the types don't already match as they are.
+    let (generics_before, substs_before) = (generics.clone(), substs.clone());
+    let (mut best, mut explained) =
+        explain::detached(|| compare_type(q, i, krate, profile, generics, substs, true));
+    for (coercion, reason) in coercions {
+        if !best.is_empty() && Similarities(best.clone()).score_with(profile) == 0.0 {
+            break;
+        }
+        let mut coerced_generics = generics_before.clone();
+        let mut coerced_substs = substs_before.clone();
+        let (q, i) = match &coercion {
+            Coerced::Query(coerced) => (coerced, i),
+            Coerced::Candidate(coerced) => (q, coerced),
+        };
+        let (mut sims, coerced_explained) = explain::detached(|| {
+            compare_type(
+                q,
+                i,
+                krate,
+                profile,
+                &mut coerced_generics,
+                &mut coerced_substs,
+                true,
+            )
+        });
+        sims.push(Discrete {
+            kind: Subequal,
+            reason,
+            category: Category::Wrapper,
+        });
+        trace!(?coercion, ?sims);
+        if closer(&sims, &best, profile) {
+            best = sims;
+            explained = coerced_explained;
+            *generics = coerced_generics;
+            *substs = coerced_substs;
+        }
+    }
+    explain::attach(explained);
+    best
+}
+
+/// Whether `sims` score better than `than` under `profile`. Comparisons that found nothing are
+/// never better, as there is nothing to rank them by.
+fn closer(sims: &[Similarity], than: &[Similarity], profile: &ScoringProfile) -> bool {
+    let score = |sims: &[Similarity]| Similarities(sims.to_vec()).score_with(profile);
+    !sims.is_empty() && (than.is_empty() || score(sims) < score(than))
+}
+
+/// Either side of a comparison, replaced by the type it coerces to.
+#[derive(Debug)]
+enum Coerced {
+    Query(Type),
+    Candidate(types::Type),
+}
+
+/// Std types that deref to another type, found by name as their ids are not known.
+///
+/// `String`, `PathBuf`, `OsString` and `CString` deref to the type named next to them, and
+/// the rest to their first type argument, or a slice of it for `Vec`.
+const STD_DEREFS: [(&str, Option<&str>); 10] = [
+    ("String", Some("str")),
+    ("PathBuf", Some("Path")),
+    ("OsString", Some("OsStr")),
+    ("CString", Some("CStr")),
+    ("Vec", None),
+    ("Box", None),
+    ("Rc", None),
+    ("Arc", None),
+    ("Cow", None),
+    ("ManuallyDrop", None),
+];
+
+/// The types `q` and `i` may stand for instead through `Deref`, `AsRef` or `Borrow`, with the
+/// reason to report for each.
+///
+/// Query types are only coerced by [`STD_DEREFS`]. Candidate types are also coerced by the
+/// impls of these traits in `krate`, but not to std types that can't be named without an id.
+fn coercions(q: &Type, i: &types::Type, krate: &Crate) -> Vec<(Coerced, SimilarityReason)> {
+    let mut coercions = vec![];
+
+    if let Type::UnresolvedPath { name, args } = q {
+        let name = name.rsplit("::").next().unwrap_or(name);
+        let arg = || match args.as_deref() {
+            Some(GenericArgs::AngleBracketed { args, .. }) => {
+                args.iter().find_map(|arg| match arg {
+                    Some(GenericArg::Type(ty)) => Some(ty.clone()),
+                    _ => None,
+                })
+            }
+            _ => None,
+        };
+        let target = match STD_DEREFS.iter().find(|(std, _)| *std == name) {
+            Some((_, Some("str"))) => Some(Type::Primitive(PrimitiveType::Str)),
+            Some((_, Some(target))) => Some(Type::UnresolvedPath {
+                name: target.to_string(),
+                args: None,
+            }),
+            Some(("Vec", None)) => Some(Type::Slice(arg().map(Box::new))),
+            Some((_, None)) => arg(),
+            None => None,
+        };
+        if let Some(target) = target {
+            coercions.push((Coerced::Query(target), SimilarityReason::DerefCoercion));
+        }
+    }
+
+    let types::Type::ResolvedPath(path) = i else {
+        return coercions;
+    };
+    let args = match path.args.as_deref() {
+        Some(types::GenericArgs::AngleBracketed { args, .. }) => args.as_slice(),
+        _ => &[],
+    };
+    let arg = args.iter().find_map(|arg| match arg {
+        types::GenericArg::Type(ty) => Some(ty.clone()),
+        _ => None,
+    });
+    let name = path.path.rsplit("::").next().unwrap_or(&path.path);
+    let target = match STD_DEREFS.iter().find(|(std, _)| *std == name) {
+        Some((_, Some("str"))) => Some(types::Type::Primitive("str".to_owned())),
+        Some(("Vec", None)) => arg.map(|arg| types::Type::Slice(Box::new(arg))),
+        Some((_, None)) => arg,
+        _ => None,
+    };
+    if let Some(target) = target {
+        coercions.push((Coerced::Candidate(target), SimilarityReason::DerefCoercion));
+    }
+
+    let impls = match krate.index.get(&path.id).map(|item| &item.inner) {
+        Some(types::ItemEnum::Struct(s)) => &s.impls,
+        Some(types::ItemEnum::Enum(e)) => &e.impls,
+        Some(types::ItemEnum::Union(u)) => &u.impls,
+        _ => return coercions,
+    };
+    for impl_ in impls
+        .iter()
+        .filter_map(|id| match krate.index.get(id)?.inner {
+            types::ItemEnum::Impl(ref impl_) => Some(impl_),
+            _ => None,
+        })
+    {
+        // Blanket impls such as `impl<T> Borrow<T> for T` coerce nothing.
+        let (Some(trait_), None) = (&impl_.trait_, &impl_.blanket_impl) else {
+            continue;
+        };
+        let (target, reason) = match trait_.path.rsplit("::").next() {
+            Some("Deref") => {
+                let target = impl_
+                    .items
+                    .iter()
+                    .find_map(|id| match krate.index.get(id)? {
+                        types::Item {
+                            name: Some(name),
+                            inner:
+                                types::ItemEnum::AssocType {
+                                    type_: Some(type_), ..
+                                },
+                            ..
+                        } if name == "Target" => Some(type_),
+                        _ => None,
+                    });
+                (target, SimilarityReason::DerefCoercion)
+            }
+            Some(name @ ("AsRef" | "Borrow")) => {
+                let target = match trait_.args.as_deref() {
+                    Some(types::GenericArgs::AngleBracketed { args, .. }) => {
+                        args.iter().find_map(|arg| match arg {
+                            types::GenericArg::Type(ty) => Some(ty),
+                            _ => None,
+                        })
+                    }
+                    _ => None,
+                };
+                let reason = if name == "AsRef" {
+                    SimilarityReason::AsRefConversion
+                } else {
+                    SimilarityReason::BorrowConversion
+                };
+                (target, reason)
+            }
+            _ => continue,
+        };
+        let Some(target) = target else {
+            continue;
+        };
+
+        // The generics of the impl stand for the arguments of `path` they are written at.
+        let impl_args = match &impl_.for_ {
+            types::Type::ResolvedPath(types::Path {
+                args: Some(args), ..
+            }) => match &**args {
+                types::GenericArgs::AngleBracketed { args, .. } => args.as_slice(),
+                _ => &[],
+            },
+            _ => &[],
+        };
+        let params = impl_args
+            .iter()
+            .zip(args)
+            .filter_map(|(impl_arg, arg)| match (impl_arg, arg) {
+                (
+                    types::GenericArg::Type(types::Type::Generic(name)),
+                    types::GenericArg::Type(ty),
+                ) => Some((name.as_str(), ty)),
+                _ => None,
+            })
+            .collect::<HashMap<_, _>>();
+        coercions.push((
+            Coerced::Candidate(substitute_generics(target, &params)),
+            reason,
+        ));
+    }
+
+    coercions
 }
 
 impl Compare<types::Term> for Type {
@@ -588,11 +3038,12 @@ impl Compare<types::Term> for Type {
         &self,
         type_: &types::Term,
         krate: &Crate,
+        profile: &ScoringProfile,
         generics: &mut Generics,
         substs: &mut HashMap<String, Type>,
     ) -> Vec<Similarity> {
         match type_ {
-            types::Term::Type(i) => compare_type(self, i, krate, generics, substs, true),
+            types::Term::Type(i) => compare_type(self, i, krate, profile, generics, substs, true),
             _ => todo!("comparing Type with non-Type Term is not supported yet"),
         }
     }
@@ -604,18 +3055,21 @@ impl Compare<String> for PrimitiveType {
         &self,
         prim_ty: &String,
         _: &Crate,
+        _: &ScoringProfile,
         _: &mut Generics,
         _: &mut HashMap<String, Type>,
     ) -> Vec<Similarity> {
         if self.as_str() == prim_ty {
             vec![Discrete {
                 kind: Equivalent,
-                reason: "primitive matches".to_string(),
+                reason: SimilarityReason::PrimitiveMatches,
+                category: Category::Other,
             }]
         } else {
             vec![Discrete {
                 kind: Different,
-                reason: "primitive differs".to_string(),
+                reason: SimilarityReason::PrimitiveDiffers,
+                category: Category::Other,
             }]
         }
     }
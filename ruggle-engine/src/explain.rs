@@ -0,0 +1,159 @@
+//! Explanations of comparisons, as trees mirroring the parts of the query being compared.
+//!
+//! Comparisons record their parts with [`record`] only while [`explain`] runs, so searching
+//! without explanations only pays for checking whether one is being recorded.
+
+use std::{cell::RefCell, fmt};
+
+use serde::{Deserialize, Serialize};
+
+use crate::compare::{ScoringProfile, Similarities, Similarity};
+
+/// A part of the query, e.g. an argument or a generic arg of a type, and how it compared.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Explanation {
+    /// What was compared, e.g. `argument 0` or `` `Vec<T>` ~ `Vec<u8>` ``.
+    pub label: String,
+    /// Score of every similarity found under this part under the profile the comparison ranked
+    /// with, `None` if there are none.
+    pub score: Option<f32>,
+    /// Similarities found by this part itself, rather than by one of its children.
+    pub similarities: Vec<ScoredSimilarity>,
+    pub children: Vec<Explanation>,
+}
+
+/// A similarity of an [`Explanation`], with its score under the same profile as the part.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct ScoredSimilarity {
+    pub score: f32,
+    pub similarity: Similarity,
+}
+
+impl Explanation {
+    fn fmt_indented(&self, f: &mut fmt::Formatter<'_>, depth: usize) -> fmt::Result {
+        let indent = "  ".repeat(depth);
+        match self.score {
+            Some(score) => writeln!(f, "{}{:.2} {}", indent, score, self.label)?,
+            None => writeln!(f, "{}   - {}", indent, self.label)?,
+        }
+        for sim in &self.similarities {
+            writeln!(
+                f,
+                "{}  {:.2} ({})",
+                indent,
+                sim.score,
+                sim.similarity.reason()
+            )?;
+        }
+        for child in &self.children {
+            child.fmt_indented(f, depth + 1)?;
+        }
+        Ok(())
+    }
+}
+
+/// Renders the tree with each part indented under its parent, followed by its own similarities.
+impl fmt::Display for Explanation {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        self.fmt_indented(f, 0)
+    }
+}
+
+/// A recorded part, along with every similarity it returned to tell its parent's own apart.
+pub(crate) struct Recorded {
+    node: Explanation,
+    returned: Vec<Similarity>,
+}
+
+impl Recorded {
+    pub(crate) fn into_explanation(self) -> Explanation {
+        self.node
+    }
+}
+
+thread_local! {
+    /// Parts recorded at each level of the comparison being explained, empty otherwise.
+    static FRAMES: RefCell<Vec<Vec<Recorded>>> = const { RefCell::new(Vec::new()) };
+}
+
+fn recording() -> bool {
+    FRAMES.with(|frames| !frames.borrow().is_empty())
+}
+
+/// Run `compare`, returning the explanations of the parts it recorded along with its result.
+pub fn explain<R>(compare: impl FnOnce() -> R) -> (R, Vec<Explanation>) {
+    FRAMES.with(|frames| frames.borrow_mut().push(vec![]));
+    let result = compare();
+    let recorded = FRAMES.with(|frames| frames.borrow_mut().pop().unwrap_or_default());
+    (result, recorded.into_iter().map(|r| r.node).collect())
+}
+
+/// Record `compare` as a part of the comparison labelled `label`, scored with `profile`, if one
+/// is being explained.
+pub(crate) fn record(
+    profile: &ScoringProfile,
+    label: impl FnOnce() -> String,
+    compare: impl FnOnce() -> Vec<Similarity>,
+) -> Vec<Similarity> {
+    if !recording() {
+        return compare();
+    }
+    let (sims, children) = detached(compare);
+
+    let mut own = sims.clone();
+    for sim in children.iter().flat_map(|child| &child.returned) {
+        // Categories may be changed by the parent, reasons and scores are not.
+        if let Some(idx) = own
+            .iter()
+            .position(|own| own.reason() == sim.reason() && own.score() == sim.score())
+        {
+            own.remove(idx);
+        }
+    }
+    let node = Explanation {
+        label: label(),
+        score: (!sims.is_empty()).then(|| Similarities(sims.clone()).score_with(profile)),
+        similarities: own
+            .into_iter()
+            .map(|similarity| ScoredSimilarity {
+                score: similarity.score_with(profile),
+                similarity,
+            })
+            .collect(),
+        children: children.into_iter().map(|child| child.node).collect(),
+    };
+    attach(vec![Recorded {
+        node,
+        returned: sims.clone(),
+    }]);
+    sims
+}
+
+/// Run `compare`, holding back the parts it records so that they can be [`attach`]ed only if
+/// its result is kept, as when trying alternatives.
+pub(crate) fn detached<R>(compare: impl FnOnce() -> R) -> (R, Vec<Recorded>) {
+    if !recording() {
+        return (compare(), vec![]);
+    }
+    FRAMES.with(|frames| frames.borrow_mut().push(vec![]));
+    let result = compare();
+    let recorded = FRAMES.with(|frames| frames.borrow_mut().pop().unwrap_or_default());
+    (result, recorded)
+}
+
+/// Add parts held back by [`detached`] to the part being recorded.
+pub(crate) fn attach(recorded: Vec<Recorded>) {
+    FRAMES.with(|frames| {
+        if let Some(frame) = frames.borrow_mut().last_mut() {
+            frame.extend(recorded);
+        }
+    });
+}
+
+/// Run `compare` without recording it, for comparisons only made to decide how to compare.
+pub(crate) fn quiet<R>(compare: impl FnOnce() -> R) -> R {
+    let frames = FRAMES.with(|frames| std::mem::take(&mut *frames.borrow_mut()));
+    let result = compare();
+    FRAMES.with(|f| *f.borrow_mut() = frames);
+    result
+}
@@ -1,4 +1,6 @@
 pub mod compare;
+pub mod explain;
+pub mod names;
 pub mod query;
 pub mod search;
 pub mod types;
@@ -96,13 +98,36 @@ impl Path {
                 _ => {}
             }
             link.push_str(&format!("{}.html#", owner.name.as_deref().unwrap_or("")));
+            // Required trait methods are anchored differently from the ones with a body.
+            let anchor = match (&owner.inner, &self.item.inner) {
+                (types::ItemEnum::Trait(_), types::ItemEnum::Function(f)) if !f.has_body => {
+                    "tymethod"
+                }
+                _ => "method",
+            };
             link.push_str(&format!(
-                "method.{}",
+                "{}.{}",
+                anchor,
                 self.item.name.as_deref().unwrap_or("")
             ));
         } else {
+            let prefix = match &self.item.inner {
+                types::ItemEnum::Macro(_) => "macro",
+                types::ItemEnum::ProcMacro(m) => match m.kind {
+                    types::MacroKind::Bang => "macro",
+                    types::MacroKind::Attr => "attr",
+                    types::MacroKind::Derive => "derive",
+                },
+                types::ItemEnum::Struct(_) => "struct",
+                types::ItemEnum::Enum(_) => "enum",
+                types::ItemEnum::Union(_) => "union",
+                types::ItemEnum::Trait(_) => "trait",
+                types::ItemEnum::TypeAlias(_) => "type",
+                _ => "fn",
+            };
             link.push_str(&format!(
-                "fn.{}.html",
+                "{}.{}.html",
+                prefix,
                 self.item.name.as_deref().unwrap_or("")
             ));
         }
@@ -138,8 +163,9 @@ pub fn build_parent_index(krate: &types::Crate) -> HashMap<types::Id, Parent> {
                 for child in &t.items {
                     parent.insert(*child, Parent::Trait(*id));
                 }
+                // Impls on local structs and enums stay owned by them, as their arms overwrite this.
                 for child in &t.implementations {
-                    parent.insert(*child, Parent::Trait(*id));
+                    parent.entry(*child).or_insert(Parent::Trait(*id));
                 }
             }
             types::ItemEnum::Impl(i) => {
@@ -169,8 +195,8 @@ pub fn reconstruct_path_for_local(
     let item = krate.index.get(&cur).unwrap().clone();
 
     assert!(
-        matches!(item.inner, types::ItemEnum::Function(_) | types::ItemEnum::Trait(_) | types::ItemEnum::Impl(_) | types::ItemEnum::Struct(_) | types::ItemEnum::Enum(_) | types::ItemEnum::Union(_) | types::ItemEnum::TypeAlias(_) | types::ItemEnum::Primitive(_) ),
-        "ruggle_engine::reconstruct_path_for_local is only expected to be called with functions, traits, impls, structs, enums, unions, type aliases, or primitives, got {:?}",
+        matches!(item.inner, types::ItemEnum::Function(_) | types::ItemEnum::Trait(_) | types::ItemEnum::Impl(_) | types::ItemEnum::Struct(_) | types::ItemEnum::Enum(_) | types::ItemEnum::Union(_) | types::ItemEnum::TypeAlias(_) | types::ItemEnum::Primitive(_) | types::ItemEnum::Macro(_) | types::ItemEnum::ProcMacro(_) ),
+        "ruggle_engine::reconstruct_path_for_local is only expected to be called with functions, traits, impls, structs, enums, unions, type aliases, primitives, or macros, got {:?}",
         item
     );
 
@@ -0,0 +1,202 @@
+//! Matching of query names against item and type names.
+//!
+//! Comparisons match names with the [`NameMatching`] of their scoring profile, see
+//! [`crate::compare::ScoringProfile::names`].
+
+use std::cmp::max;
+
+use levenshtein::levenshtein;
+use serde::{Deserialize, Serialize};
+
+/// A strategy for scoring how far a query name is from a candidate name.
+pub trait NameMatcher {
+    /// Distance between `query` and `symbol`, from 0 (same name) to 1 (unrelated names).
+    fn distance(&self, query: &str, symbol: &str) -> f32;
+}
+
+/// Normalized edit distance between the names as they are written.
+#[derive(Debug, Clone, Copy, Default)]
+pub struct Levenshtein;
+
+impl NameMatcher for Levenshtein {
+    fn distance(&self, query: &str, symbol: &str) -> f32 {
+        match max(query.len(), symbol.len()) {
+            0 => 0.0,
+            len => levenshtein(query, symbol) as f32 / len as f32,
+        }
+    }
+}
+
+/// Share of the distance of a [`Tokenized`] match coming from query tokens, the rest coming from
+/// candidate tokens left unmatched.
+const QUERY_TOKENS: f32 = 0.75;
+/// Distance between two tokens of the same synonym group.
+const SYNONYM: f32 = 0.1;
+/// Largest normalized edit distance between two tokens still considered a typo of one another.
+const TYPO: f32 = 0.34;
+
+/// Matches names word by word, splitting them on underscores and case changes, so that
+/// `split` is close to `split_whitespace` while `to_str` is far from `as_str`.
+///
+/// Each query token is matched to its closest candidate token, rewarding exact tokens, then
+/// synonyms, prefixes, substrings and typos, in that order.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Tokenized {
+    /// Groups of tokens standing for the same thing, e.g. `len`, `size` and `count`.
+    #[serde(default = "default_synonyms")]
+    pub synonyms: Vec<Vec<String>>,
+}
+
+impl Default for Tokenized {
+    fn default() -> Self {
+        Self {
+            synonyms: default_synonyms(),
+        }
+    }
+}
+
+fn default_synonyms() -> Vec<Vec<String>> {
+    [&["len", "size", "count"][..], &["new", "create"]]
+        .iter()
+        .map(|group| group.iter().map(|token| token.to_string()).collect())
+        .collect()
+}
+
+impl Tokenized {
+    fn synonymous(&self, q: &str, i: &str) -> bool {
+        self.synonyms.iter().any(|group| {
+            group.iter().any(|token| token == q) && group.iter().any(|token| token == i)
+        })
+    }
+
+    fn token_distance(&self, q: &str, i: &str) -> f32 {
+        let (shorter, longer) = if q.len() < i.len() { (q, i) } else { (i, q) };
+        // How much of the longer token is left uncovered by the shorter one.
+        let uncovered = 1.0 - shorter.len() as f32 / longer.len() as f32;
+        if q == i {
+            0.0
+        } else if self.synonymous(q, i) {
+            SYNONYM
+        } else if longer.starts_with(shorter) {
+            0.5 * uncovered
+        } else if longer.contains(shorter) {
+            0.25 + 0.5 * uncovered
+        } else {
+            match Levenshtein.distance(q, i) {
+                typo if typo <= TYPO => typo,
+                _ => 1.0,
+            }
+        }
+    }
+}
+
+impl NameMatcher for Tokenized {
+    fn distance(&self, query: &str, symbol: &str) -> f32 {
+        let (q, i) = (tokens(query), tokens(symbol));
+        if q.is_empty() || i.is_empty() {
+            return Levenshtein.distance(query, symbol);
+        }
+
+        let mut matched = vec![false; i.len()];
+        let mut distance = 0.0;
+        for q in &q {
+            let (k, d) = i
+                .iter()
+                .map(|i| self.token_distance(q, i))
+                .enumerate()
+                .min_by(|(_, a), (_, b)| a.total_cmp(b))
+                .unwrap(); // SAFETY: `i` is not empty.
+            matched[k] |= d < 1.0;
+            distance += d;
+        }
+        let unmatched = matched.iter().filter(|matched| !**matched).count();
+
+        QUERY_TOKENS * distance / q.len() as f32
+            + (1.0 - QUERY_TOKENS) * unmatched as f32 / i.len() as f32
+    }
+}
+
+/// Lowercase words of an identifier, split on non-alphanumeric characters and on case changes,
+/// e.g. `HTTPServer_new` to `http`, `server` and `new`.
+fn tokens(name: &str) -> Vec<String> {
+    let chars = name.chars().collect::<Vec<_>>();
+    let mut tokens = vec![];
+    let mut token = String::new();
+    for (k, &c) in chars.iter().enumerate() {
+        if !c.is_alphanumeric() {
+            tokens.extend((!token.is_empty()).then(|| std::mem::take(&mut token)));
+            continue;
+        }
+        let prev = k.checked_sub(1).map(|k| chars[k]);
+        let next = chars.get(k + 1);
+        let boundary = c.is_uppercase()
+            && prev.is_some_and(|prev| {
+                prev.is_lowercase()
+                    || prev.is_numeric()
+                    || prev.is_uppercase() && next.is_some_and(|next| next.is_lowercase())
+            });
+        if boundary && !token.is_empty() {
+            tokens.push(std::mem::take(&mut token));
+        }
+        token.extend(c.to_lowercase());
+    }
+    tokens.extend((!token.is_empty()).then_some(token));
+    tokens
+}
+
+/// The strategies a scoring profile can pick, e.g. in TOML:
+///
+/// ```toml
+/// [names]
+/// strategy = "tokenized"
+/// synonyms = [["len", "size", "count"], ["new", "create"]]
+/// ```
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(tag = "strategy", rename_all = "snake_case")]
+pub enum NameMatching {
+    Levenshtein,
+    Tokenized(Tokenized),
+}
+
+impl Default for NameMatching {
+    fn default() -> Self {
+        NameMatching::Tokenized(Tokenized::default())
+    }
+}
+
+impl NameMatcher for NameMatching {
+    fn distance(&self, query: &str, symbol: &str) -> f32 {
+        match self {
+            NameMatching::Levenshtein => Levenshtein.distance(query, symbol),
+            NameMatching::Tokenized(names) => names.distance(query, symbol),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn tokenize_names() {
+        assert_eq!(tokens("split_whitespace"), ["split", "whitespace"]);
+        assert_eq!(tokens("HTTPServer"), ["http", "server"]);
+        assert_eq!(tokens("IpAddrV4"), ["ip", "addr", "v4"]);
+        assert_eq!(tokens("__as_str__"), ["as", "str"]);
+    }
+
+    #[test]
+    fn match_tokens() {
+        let names = Tokenized::default();
+        assert_eq!(names.distance("split_words", "split_words"), 0.0);
+        assert_eq!(names.distance("SplitWords", "split_words"), 0.0);
+        assert!(names.distance("split", "split_whitespace") < 0.2);
+        assert!(names.distance("to_str", "as_str") > Levenshtein.distance("to_str", "as_str"));
+        // Exact tokens are closer than prefixes, which are closer than substrings.
+        assert!(names.distance("len", "len") < names.distance("len", "length"));
+        assert!(names.distance("len", "length") < names.distance("len", "bytelength"));
+        assert!(names.distance("len", "word_count") < 0.25);
+        assert!(names.distance("create", "new") < 0.1);
+        assert!(Tokenized { synonyms: vec![] }.distance("create", "new") == 1.0);
+    }
+}
@@ -16,53 +16,320 @@ pub struct HitItem {
 
 #[derive(Clone, Debug, Serialize, Deserialize, PartialEq)]
 pub struct Query {
+    pub scope: Option<QueryScope>,
     pub name: Option<Symbol>,
     pub kind: Option<QueryKind>,
 }
 
+/// Restricts a query to the items of a module, struct, enum or trait, e.g. the `struct Vec:` in
+/// `struct Vec: fn(&self) -> usize`.
+#[derive(Clone, Debug, Serialize, Deserialize, PartialEq)]
+pub struct QueryScope {
+    pub kind: ScopeKind,
+    pub name: Symbol,
+}
+
+impl Display for QueryScope {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let kind = match self.kind {
+            ScopeKind::Module => "mod",
+            ScopeKind::Struct => "struct",
+            ScopeKind::Enum => "enum",
+            ScopeKind::Trait => "trait",
+        };
+        write!(f, "{} {}:", kind, self.name)
+    }
+}
+
+#[derive(Clone, Copy, Debug, Serialize, Deserialize, PartialEq, Eq)]
+pub enum ScopeKind {
+    Module,
+    Struct,
+    Enum,
+    Trait,
+}
+
 impl Query {
     pub fn args(&self) -> Option<Vec<Argument>> {
         self.kind
             .as_ref()
-            .map(|kind| {
-                let QueryKind::FunctionQuery(f) = kind;
-                &f.decl
+            .and_then(|kind| match kind {
+                QueryKind::FunctionQuery(f) => Some(&f.decl),
+                _ => None,
             })
             .and_then(|decl| decl.inputs.clone())
     }
+
+    /// Whether an item of kind `item` is something this query asks for, so that e.g. function
+    /// queries are never answered with macros.
+    pub fn targets(&self, item: &crate::types::ItemEnum) -> bool {
+        use crate::types::ItemEnum;
+        match self.kind {
+            Some(QueryKind::MacroQuery(_)) => {
+                matches!(item, ItemEnum::Macro(_) | ItemEnum::ProcMacro(_))
+            }
+            Some(QueryKind::StructQuery(_)) => matches!(item, ItemEnum::Struct(_)),
+            Some(QueryKind::EnumQuery(_)) => matches!(item, ItemEnum::Enum(_)),
+            Some(QueryKind::UnionQuery(_)) => matches!(item, ItemEnum::Union(_)),
+            Some(QueryKind::TraitQuery(_)) => matches!(item, ItemEnum::Trait(_)),
+            Some(QueryKind::TypeAliasQuery(_)) => matches!(item, ItemEnum::TypeAlias(_)),
+            Some(QueryKind::FunctionQuery(_)) | None => matches!(item, ItemEnum::Function(_)),
+        }
+    }
 }
 
 impl Display for Query {
     fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        if let Some(scope) = &self.scope {
+            write!(f, "{} ", scope)?;
+        }
+        if let Some(QueryKind::MacroQuery(mac)) = &self.kind {
+            write!(f, "macro")?;
+            match mac.kind {
+                Some(MacroKind::Derive) => write!(f, " derive")?,
+                Some(MacroKind::Attr) => write!(f, " attr")?,
+                Some(MacroKind::Bang) | None => {}
+            }
+            if let Some(name) = &self.name {
+                write!(f, " {}", name)?;
+            }
+            if mac.kind == Some(MacroKind::Bang) {
+                write!(f, "!")?;
+            }
+            if let Some(fragments) = &mac.fragments {
+                write!(f, "({})", fragments.join(", "))?;
+            }
+            return Ok(());
+        }
+
+        let type_def = match &self.kind {
+            Some(QueryKind::StructQuery(def)) => Some(("struct", def)),
+            Some(QueryKind::EnumQuery(def)) => Some(("enum", def)),
+            Some(QueryKind::UnionQuery(def)) => Some(("union", def)),
+            Some(QueryKind::TraitQuery(def)) => Some(("trait", def)),
+            Some(QueryKind::TypeAliasQuery(def)) => Some(("type", def)),
+            _ => None,
+        };
+        if let Some((keyword, def)) = type_def {
+            write!(f, "{} {}", keyword, self.name.as_deref().unwrap_or("_"))?;
+            return write!(f, "{}", def);
+        }
+
         write!(f, "fn")?;
-        if let Some(name) = &self.name {
-            write!(f, " {}", name)?;
+        let receiver_type = match &self.kind {
+            Some(QueryKind::FunctionQuery(func)) => func
+                .decl
+                .receiver
+                .as_ref()
+                .and_then(|receiver| receiver.type_.as_ref()),
+            _ => None,
+        };
+        match (receiver_type, &self.name) {
+            (Some(ty), name) => write!(f, " {}.{}", ty, name.as_deref().unwrap_or("?"))?,
+            (None, Some(name)) => write!(f, " {}", name)?,
+            (None, None) => {}
         }
         if let Some(kind) = &self.kind {
             match kind {
                 QueryKind::FunctionQuery(func) => {
-                    write!(f, "{}", func.decl)?;
+                    write!(f, "{}{}", func.generics, func.decl)?;
+                    if !func.generics.where_predicates.is_empty() {
+                        let predicates: Vec<String> = func
+                            .generics
+                            .where_predicates
+                            .iter()
+                            .map(|p| p.to_string())
+                            .collect();
+                        write!(f, " where {}", predicates.join(", "))?;
+                    }
                 }
+                _ => unreachable!(),
             }
         }
         Ok(())
     }
 }
 
+/// A multi-hop query `A -> ... -> C`, asking for chains of functions that turn an `A` into a `C`.
+#[derive(Clone, Debug, Serialize, Deserialize, PartialEq)]
+pub struct ChainQuery {
+    pub from: Type,
+    pub to: Type,
+}
+
+impl Display for ChainQuery {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{} -> ... -> {}", self.from, self.to)
+    }
+}
+
+/// A boolean combination of queries and filters, e.g. `fn () -> Option<_> | fn () -> Result<_, _>`
+/// or `fn parse & !is:unsafe`.
+///
+/// `!` binds tighter than `&`, which binds tighter than `|`.
+#[derive(Clone, Debug, Serialize, Deserialize, PartialEq)]
+pub enum QueryExpr {
+    Query(Query),
+    Filter(Filter),
+    Not(Box<QueryExpr>),
+    And(Vec<QueryExpr>),
+    Or(Vec<QueryExpr>),
+}
+
+impl From<Query> for QueryExpr {
+    fn from(query: Query) -> Self {
+        QueryExpr::Query(query)
+    }
+}
+
+impl Display for QueryExpr {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let join = |exprs: &[QueryExpr], sep: &str| {
+            exprs
+                .iter()
+                .map(|e| e.to_string())
+                .collect::<Vec<_>>()
+                .join(sep)
+        };
+        match self {
+            QueryExpr::Query(query) => write!(f, "{}", query),
+            QueryExpr::Filter(filter) => write!(f, "{}", filter),
+            QueryExpr::Not(expr) => write!(f, "!{}", expr),
+            QueryExpr::And(exprs) => write!(f, "{}", join(exprs, " & ")),
+            QueryExpr::Or(exprs) => write!(f, "{}", join(exprs, " | ")),
+        }
+    }
+}
+
+/// A constraint on the items a query returns which does not take part in scoring.
+#[derive(Clone, Debug, Serialize, Deserialize, PartialEq)]
+pub enum Filter {
+    /// `kind:fn`, `kind:method`, `kind:struct`, ..
+    Kind(ItemKind),
+    /// `crate:serde`
+    Crate(Symbol),
+    /// `in:collections` or `in:domain::order`, matching consecutive modules of an item's path.
+    In(Symbol),
+    /// `is:async`, `is:unsafe` or `is:const`
+    Is(Qualifier),
+    /// `deprecated:yes` or `deprecated:no`
+    Deprecated(bool),
+}
+
+impl Display for Filter {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Filter::Kind(kind) => write!(f, "kind:{}", kind),
+            Filter::Crate(name) => write!(f, "crate:{}", name),
+            Filter::In(path) => write!(f, "in:{}", path),
+            Filter::Is(qualifier) => write!(f, "is:{}", qualifier),
+            Filter::Deprecated(true) => write!(f, "deprecated:yes"),
+            Filter::Deprecated(false) => write!(f, "deprecated:no"),
+        }
+    }
+}
+
+/// The kinds of items a `kind:` filter can select. Functions are free functions, methods are
+/// the ones declared in traits or impls.
+#[derive(Clone, Copy, Debug, Serialize, Deserialize, PartialEq, Eq)]
+pub enum ItemKind {
+    Function,
+    Method,
+    Macro,
+    Struct,
+    Enum,
+    Union,
+    Trait,
+    TypeAlias,
+}
+
+impl Display for ItemKind {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let kind = match self {
+            ItemKind::Function => "fn",
+            ItemKind::Method => "method",
+            ItemKind::Macro => "macro",
+            ItemKind::Struct => "struct",
+            ItemKind::Enum => "enum",
+            ItemKind::Union => "union",
+            ItemKind::Trait => "trait",
+            ItemKind::TypeAlias => "type",
+        };
+        write!(f, "{}", kind)
+    }
+}
+
 #[non_exhaustive]
 #[derive(Clone, Debug, Serialize, Deserialize, PartialEq)]
 pub enum QueryKind {
     FunctionQuery(Function),
+    MacroQuery(Macro),
+    StructQuery(TypeDef),
+    EnumQuery(TypeDef),
+    UnionQuery(TypeDef),
+    TraitQuery(TypeDef),
+    TypeAliasQuery(TypeDef),
 }
 
 impl Display for QueryKind {
     fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
         match self {
             QueryKind::FunctionQuery(func) => write!(f, "{}", func.decl),
+            QueryKind::MacroQuery(mac) => match &mac.fragments {
+                Some(fragments) => write!(f, "({})", fragments.join(", ")),
+                None => write!(f, "(..)"),
+            },
+            QueryKind::StructQuery(def)
+            | QueryKind::EnumQuery(def)
+            | QueryKind::UnionQuery(def)
+            | QueryKind::TraitQuery(def)
+            | QueryKind::TypeAliasQuery(def) => write!(f, "{}", def),
+        }
+    }
+}
+
+/// A type definition query, e.g. `struct Name<_>`, `trait Name` or `type Name = Vec<_>`.
+#[non_exhaustive]
+#[derive(Clone, Debug, Default, Serialize, Deserialize, PartialEq)]
+pub struct TypeDef {
+    /// Number of generic parameters, `None` when the query leaves them out.
+    pub arity: Option<usize>,
+    /// The aliased type of a `type Name = ...` query.
+    pub aliased: Option<Type>,
+}
+
+impl Display for TypeDef {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        if let Some(arity) = self.arity {
+            write!(f, "<{}>", vec!["_"; arity].join(", "))?;
         }
+        if let Some(aliased) = &self.aliased {
+            write!(f, " = {}", aliased)?;
+        }
+        Ok(())
     }
 }
 
+/// A macro query, e.g. `macro make_vec!`, `macro (expr, ty)` or `macro derive Serialize`.
+#[non_exhaustive]
+#[derive(Clone, Debug, Default, Serialize, Deserialize, PartialEq)]
+pub struct Macro {
+    /// `None` matches every kind of macro.
+    pub kind: Option<MacroKind>,
+    /// Fragment specifiers one of the `macro_rules!` matchers should use, e.g. `expr` and `ty`.
+    pub fragments: Option<Vec<Symbol>>,
+}
+
+#[derive(Clone, Copy, Debug, Serialize, Deserialize, PartialEq, Eq)]
+pub enum MacroKind {
+    /// A function-like macro, `name!(..)`.
+    Bang,
+    /// An attribute macro, `#[name]`.
+    Attr,
+    /// A derive macro, `#[derive(Name)]`.
+    Derive,
+}
+
 #[non_exhaustive]
 #[derive(Clone, Debug, Eq, Hash, Serialize, Deserialize, PartialEq)]
 pub enum Qualifier {
@@ -71,16 +338,110 @@ pub enum Qualifier {
     Const,
 }
 
+impl Display for Qualifier {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Qualifier::Async => write!(f, "async"),
+            Qualifier::Unsafe => write!(f, "unsafe"),
+            Qualifier::Const => write!(f, "const"),
+        }
+    }
+}
+
 #[non_exhaustive]
 #[derive(Clone, Debug, Serialize, Deserialize, PartialEq)]
 pub struct Function {
     pub decl: FnDecl,
     pub qualifiers: HashSet<Qualifier>,
+    pub generics: Generics,
+}
+
+/// Generic parameters declared by a function query, e.g. `<K: Hash + Eq, V>`, together with
+/// its `where` clause.
+#[derive(Clone, Debug, Default, Serialize, Deserialize, PartialEq)]
+pub struct Generics {
+    pub params: Vec<GenericParamDef>,
+    pub where_predicates: Vec<WherePredicate>,
+}
+
+impl Display for Generics {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        if self.params.is_empty() {
+            return Ok(());
+        }
+        let params: Vec<String> = self.params.iter().map(|p| p.to_string()).collect();
+        write!(f, "<{}>", params.join(", "))
+    }
+}
+
+#[derive(Clone, Debug, Serialize, Deserialize, PartialEq)]
+pub struct GenericParamDef {
+    pub name: Symbol,
+    pub bounds: Vec<GenericBound>,
+}
+
+impl Display for GenericParamDef {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", self.name)?;
+        if !self.bounds.is_empty() {
+            let bounds: Vec<String> = self.bounds.iter().map(|b| b.to_string()).collect();
+            write!(f, ": {}", bounds.join(" + "))?;
+        }
+        Ok(())
+    }
+}
+
+#[non_exhaustive]
+#[derive(Clone, Debug, Serialize, Deserialize, PartialEq)]
+pub enum GenericBound {
+    /// A trait bound, e.g. `Hash` or `Borrow<Q>`.
+    TraitBound(Type),
+}
+
+impl Display for GenericBound {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            GenericBound::TraitBound(ty) => write!(f, "{}", ty),
+        }
+    }
+}
+
+#[non_exhaustive]
+#[derive(Clone, Debug, Serialize, Deserialize, PartialEq)]
+pub enum WherePredicate {
+    /// A type constrained by a set of bounds, e.g. `I: IntoIterator<Item = T>`.
+    BoundPredicate {
+        type_: Type,
+        bounds: Vec<GenericBound>,
+    },
+}
+
+impl Display for WherePredicate {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            WherePredicate::BoundPredicate { type_, bounds } => {
+                let bounds: Vec<String> = bounds.iter().map(|b| b.to_string()).collect();
+                write!(f, "{}: {}", type_, bounds.join(" + "))
+            }
+        }
+    }
 }
 
 impl Display for FnDecl {
     fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
-        write!(f, "(")?;
+        let (open, close) = if self.unordered {
+            ("{", "}")
+        } else {
+            ("(", ")")
+        };
+        write!(f, "{}", open)?;
+        let mode = self.receiver.as_ref().and_then(|receiver| receiver.mode);
+        if let Some(mode) = mode {
+            write!(f, "{}", mode)?;
+            if self.inputs.as_ref().is_none_or(|inputs| !inputs.is_empty()) {
+                write!(f, ", ")?;
+            }
+        }
         if let Some(inputs) = &self.inputs {
             let input_strs: Vec<String> = inputs
                 .iter()
@@ -95,7 +456,7 @@ impl Display for FnDecl {
         } else {
             write!(f, "..")?;
         }
-        write!(f, ")")?;
+        write!(f, "{}", close)?;
         if let Some(output) = &self.output {
             match output {
                 FnRetTy::Return(ty) => write!(f, " -> {}", ty)?,
@@ -110,26 +471,114 @@ impl Display for FnDecl {
 #[derive(Clone, Debug, Serialize, Deserialize, PartialEq)]
 pub enum GenericArgs {
     AngleBracketed {
-        args: Vec<Option<GenericArg>>, /* bindings: Vec<TypeBinding> */
+        args: Vec<Option<GenericArg>>,
+        constraints: Vec<AssocItemConstraint>,
+    },
+    /// Arguments of the `Fn*` traits, e.g. `(&T) -> bool` in `FnMut(&T) -> bool`.
+    Parenthesized {
+        inputs: Vec<Type>,
+        output: Option<Type>,
     },
-    // Parenthesized { inputs: Vec<Type>, output: Option<Type> },
 }
 
 #[non_exhaustive]
 #[derive(Clone, Debug, Serialize, Deserialize, PartialEq)]
 pub enum GenericArg {
-    // Lifetime(String),
+    /// A lifetime, e.g. `'a`. Lifetimes are parsed but not compared.
+    Lifetime(String),
     Type(Type),
-    // Const(Constant),
+    /// A const argument, e.g. `32` or `{ N + 1 }`. Const generic parameters such as `N` parse as
+    /// [`Type::Generic`], as they can't be told apart from type parameters.
+    Const(String),
 }
+
+impl Display for GenericArgs {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            GenericArgs::AngleBracketed { args, constraints } => {
+                let args = args
+                    .iter()
+                    .map(|arg| arg.as_ref().map_or("_".to_string(), |arg| arg.to_string()))
+                    .chain(
+                        constraints
+                            .iter()
+                            .map(|c| format!("{} = {}", c.name, c.term)),
+                    );
+                write!(f, "<{}>", args.collect::<Vec<_>>().join(", "))
+            }
+            GenericArgs::Parenthesized { inputs, output } => {
+                let inputs: Vec<String> = inputs.iter().map(|ty| ty.to_string()).collect();
+                write!(f, "({})", inputs.join(", "))?;
+                if let Some(output) = output {
+                    write!(f, " -> {}", output)?;
+                }
+                Ok(())
+            }
+        }
+    }
+}
+
+impl Display for GenericArg {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            GenericArg::Lifetime(lifetime) => write!(f, "{}", lifetime),
+            GenericArg::Type(ty) => write!(f, "{}", ty),
+            GenericArg::Const(c) => write!(f, "{}", c),
+        }
+    }
+}
+
+/// An associated item constraint inside generic args, e.g. the `Item = T` in
+/// `IntoIterator<Item = T>`.
+#[derive(Clone, Debug, Serialize, Deserialize, PartialEq)]
+pub struct AssocItemConstraint {
+    pub name: Symbol,
+    pub term: Type,
+}
+
 #[non_exhaustive]
 #[derive(Clone, Debug, Serialize, Deserialize, PartialEq)]
 pub struct FnDecl {
     pub inputs: Option<Vec<Argument>>,
     pub output: Option<FnRetTy>,
+    /// Whether `inputs` may match the arguments of a function in any order, as in
+    /// `fn {usize, &str}`.
+    #[serde(default)]
+    pub unordered: bool,
+    /// The `self` a method must take, as in `fn (&mut self, T)` or `Vec<u8>.len()`. It is not
+    /// part of `inputs`.
+    #[serde(default)]
+    pub receiver: Option<Box<Receiver>>,
     // pub c_variadic: bool,
 }
 
+#[derive(Clone, Debug, Serialize, Deserialize, PartialEq)]
+pub struct Receiver {
+    /// `None` when only the type of `self` is given, as in `Vec<u8>.len()`.
+    pub mode: Option<ReceiverMode>,
+    pub type_: Option<Type>,
+}
+
+#[derive(Clone, Copy, Debug, Serialize, Deserialize, PartialEq, Eq)]
+pub enum ReceiverMode {
+    /// `self`
+    Value,
+    /// `&self`
+    Ref,
+    /// `&mut self`
+    RefMut,
+}
+
+impl Display for ReceiverMode {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ReceiverMode::Value => write!(f, "self"),
+            ReceiverMode::Ref => write!(f, "&self"),
+            ReceiverMode::RefMut => write!(f, "&mut self"),
+        }
+    }
+}
+
 #[derive(Clone, Debug, Serialize, Deserialize, PartialEq)]
 pub struct Argument {
     pub ty: Option<Type>,
@@ -156,6 +605,11 @@ pub enum Type {
     Primitive(PrimitiveType),
     Tuple(Vec<Option<Type>>),
     Slice(Option<Box<Type>>),
+    /// An array, e.g. `[u8; 32]` or `[T; N]`. `None` stands for `_`.
+    Array {
+        type_: Option<Box<Type>>,
+        len: Option<String>,
+    },
     Never,
     RawPointer {
         mutable: bool,
@@ -165,6 +619,22 @@ pub enum Type {
         mutable: bool,
         type_: Box<Type>,
     },
+    /// An opaque type, e.g. `impl Iterator<Item = u8>`.
+    ImplTrait(Vec<GenericBound>),
+    /// A trait object, e.g. `dyn Error + Send`.
+    DynTrait(Vec<GenericBound>),
+    /// A function pointer, e.g. `fn(i32) -> i32`.
+    FunctionPointer(Box<FnDecl>),
+    /// An associated type projection, e.g. `T::Item` or `<T as Iterator>::Item`.
+    QualifiedPath {
+        name: Symbol,
+        self_type: Box<Type>,
+        /// `None` when the trait is left out, as in `T::Item`.
+        trait_: Option<Box<Type>>,
+    },
+    /// A named hole, e.g. the `?a` in `fn (Vec<?a>, ?a) -> bool`, which matches any type as long
+    /// as every occurrence of it matches the same one.
+    Hole(Symbol),
 }
 
 impl Display for Type {
@@ -173,7 +643,7 @@ impl Display for Type {
         match self {
             UnresolvedPath { name, args } => {
                 if let Some(args) = args {
-                    write!(f, "{}<{:?}>", name, args)
+                    write!(f, "{}{}", name, args)
                 } else {
                     write!(f, "{}", name)
                 }
@@ -194,6 +664,12 @@ impl Display for Type {
                 Some(ty) => write!(f, "[{}]", ty),
                 None => write!(f, "[_]",),
             },
+            Array { type_, len } => {
+                let type_ = type_
+                    .as_ref()
+                    .map_or("_".to_string(), |ty| format!("{}", ty));
+                write!(f, "[{}; {}]", type_, len.as_deref().unwrap_or("_"))
+            }
             Never => write!(f, "!"),
             RawPointer { mutable, type_ } => {
                 if *mutable {
@@ -209,6 +685,27 @@ impl Display for Type {
                     write!(f, "&{}", type_)
                 }
             }
+            ImplTrait(bounds) | DynTrait(bounds) => {
+                let bounds: Vec<String> = bounds.iter().map(|b| format!("{}", b)).collect();
+                let keyword = if matches!(self, ImplTrait(_)) {
+                    "impl"
+                } else {
+                    "dyn"
+                };
+                write!(f, "{} {}", keyword, bounds.join(" + "))
+            }
+            FunctionPointer(decl) => write!(f, "fn{}", decl),
+            QualifiedPath {
+                name,
+                self_type,
+                trait_: Some(trait_),
+            } => write!(f, "<{} as {}>::{}", self_type, trait_, name),
+            QualifiedPath {
+                name,
+                self_type,
+                trait_: None,
+            } => write!(f, "{}::{}", self_type, name),
+            Hole(name) => write!(f, "?{}", name),
         }
     }
 }
@@ -249,6 +746,32 @@ pub enum PrimitiveType {
 }
 
 impl PrimitiveType {
+    /// Inverse of [`PrimitiveType::as_str`] for the primitives rustdoc names.
+    pub fn from_name(name: &str) -> Option<Self> {
+        use PrimitiveType::*;
+        let prim = match name {
+            "isize" => Isize,
+            "i8" => I8,
+            "i16" => I16,
+            "i32" => I32,
+            "i64" => I64,
+            "i128" => I128,
+            "usize" => Usize,
+            "u8" => U8,
+            "u16" => U16,
+            "u32" => U32,
+            "u64" => U64,
+            "u128" => U128,
+            "f32" => F32,
+            "f64" => F64,
+            "char" => Char,
+            "bool" => Bool,
+            "str" => Str,
+            _ => return None,
+        };
+        Some(prim)
+    }
+
     pub fn as_str(&self) -> &str {
         use PrimitiveType::*;
         match self {
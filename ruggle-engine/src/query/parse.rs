@@ -1,21 +1,307 @@
 use nom::{
     branch::alt,
-    bytes::complete::{tag, take_while1},
+    bytes::complete::{tag, take_while, take_while1},
     character::complete::char,
-    character::complete::{alpha1, alphanumeric1, multispace0, multispace1},
-    combinator::{eof, fail, map, not, opt, recognize, value},
-    error::{ContextError, ParseError},
-    multi::{many0, separated_list0},
-    sequence::{delimited, pair, preceded},
+    character::complete::{alpha1, alphanumeric1, digit1, multispace0, multispace1},
+    combinator::{
+        all_consuming, consumed, cut, eof, fail, map, map_opt, not, opt, recognize, value, verify,
+    },
+    error::{context, ContextError, ErrorKind, ParseError, VerboseError, VerboseErrorKind},
+    multi::{many0, separated_list0, separated_list1},
+    sequence::{delimited, pair, preceded, terminated, tuple},
     IResult,
 };
 
+use serde::{Deserialize, Serialize};
+
 use crate::query::*;
 
 type Symbol = String;
 
 pub fn parse_query(i: &str) -> IResult<&str, Query> {
-    parse_function_query(i)
+    query(i)
+}
+
+pub fn parse_query_expr(i: &str) -> IResult<&str, QueryExpr> {
+    query_expr(i)
+}
+
+pub fn parse_chain_query(i: &str) -> IResult<&str, ChainQuery> {
+    chain_query(i)
+}
+
+/// Parses the whole of `i` as a query, failing with the position and expectations of the first
+/// unparsable token instead of ignoring trailing input like [`parse_query`] does.
+pub fn parse_query_strict(i: &str) -> Result<Query, QueryParseError> {
+    strict(i, query)
+}
+
+/// Like [`parse_query_strict`], for queries combined with `|`, `&` and `!` and filters.
+pub fn parse_query_expr_strict(i: &str) -> Result<QueryExpr, QueryParseError> {
+    strict(i, query_expr)
+}
+
+/// Like [`parse_query_strict`], for multi-hop queries.
+pub fn parse_chain_query_strict(i: &str) -> Result<ChainQuery, QueryParseError> {
+    strict(i, chain_query)
+}
+
+/// A query that failed to parse, pointing at where parsing stopped.
+///
+/// Its [`Display`](std::fmt::Display) implementation underlines the offending position:
+///
+/// ```text
+/// parsing query failed at byte 16, expected type
+///   fn (Vec<u8>) ->
+///                   ^
+/// ```
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub struct QueryParseError {
+    pub query: String,
+    /// Byte offset into `query` at which parsing failed.
+    pub offset: usize,
+    /// What the parser expected at `offset`, e.g. `type` or `` `)` ``.
+    pub expected: Vec<String>,
+}
+
+impl QueryParseError {
+    fn new(query: &str, error: VerboseError<&str>) -> Self {
+        // The error that got the furthest is the most telling one.
+        let remaining = error
+            .errors
+            .iter()
+            .map(|(i, _)| i.len())
+            .min()
+            .unwrap_or_default();
+
+        // A context names what was being parsed, which reads better than the tokens tried.
+        let expected = match error.errors.iter().find_map(|(_, kind)| match kind {
+            VerboseErrorKind::Context(context) => Some(context.to_string()),
+            _ => None,
+        }) {
+            Some(context) => vec![context],
+            None => {
+                let mut expected = vec![];
+                for (_, kind) in error.errors.iter().filter(|(i, _)| i.len() == remaining) {
+                    let token = match kind {
+                        VerboseErrorKind::Char(c) => format!("`{}`", c),
+                        VerboseErrorKind::Nom(ErrorKind::Eof) => "end of query".to_string(),
+                        _ => continue,
+                    };
+                    if !expected.contains(&token) {
+                        expected.push(token);
+                    }
+                }
+                expected
+            }
+        };
+
+        QueryParseError {
+            query: query.to_owned(),
+            offset: query.len() - remaining,
+            expected,
+        }
+    }
+}
+
+impl std::fmt::Display for QueryParseError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "parsing query failed at byte {}", self.offset)?;
+        if !self.expected.is_empty() {
+            write!(f, ", expected {}", self.expected.join(" or "))?;
+        }
+        let column = self.query[..self.offset].chars().count();
+        write!(f, "\n  {}\n  {}^", self.query, " ".repeat(column))
+    }
+}
+
+impl std::error::Error for QueryParseError {}
+
+fn strict<'a, O>(
+    i: &'a str,
+    parser: impl FnMut(&'a str) -> IResult<&'a str, O, VerboseError<&'a str>>,
+) -> Result<O, QueryParseError> {
+    match all_consuming(terminated(parser, multispace0))(i) {
+        Ok((_, o)) => Ok(o),
+        Err(nom::Err::Error(e) | nom::Err::Failure(e)) => Err(QueryParseError::new(i, e)),
+        // Only streaming parsers ask for more input.
+        Err(nom::Err::Incomplete(_)) => unreachable!(),
+    }
+}
+
+fn query<'a, E>(i: &'a str) -> IResult<&'a str, Query, E>
+where
+    E: ParseError<&'a str> + ContextError<&'a str>,
+{
+    let (i, scope) = opt(parse_scope)(i)?;
+    let (i, query) = alt((
+        parse_macro_query,
+        parse_type_def_query,
+        parse_function_query,
+    ))(i)?;
+
+    Ok((i, Query { scope, ..query }))
+}
+
+fn query_expr<'a, E>(i: &'a str) -> IResult<&'a str, QueryExpr, E>
+where
+    E: ParseError<&'a str> + ContextError<&'a str>,
+{
+    map(
+        pair(
+            parse_conjunction,
+            many0(preceded(
+                preceded(multispace0, char('|')),
+                cut(parse_conjunction),
+            )),
+        ),
+        |(first, rest)| combine(first, rest, QueryExpr::Or),
+    )(i)
+}
+
+fn parse_conjunction<'a, E>(i: &'a str) -> IResult<&'a str, QueryExpr, E>
+where
+    E: ParseError<&'a str> + ContextError<&'a str>,
+{
+    map(
+        pair(
+            parse_negation,
+            many0(preceded(
+                preceded(multispace0, char('&')),
+                cut(parse_negation),
+            )),
+        ),
+        |(first, rest)| combine(first, rest, QueryExpr::And),
+    )(i)
+}
+
+fn combine(
+    first: QueryExpr,
+    mut rest: Vec<QueryExpr>,
+    op: fn(Vec<QueryExpr>) -> QueryExpr,
+) -> QueryExpr {
+    if rest.is_empty() {
+        first
+    } else {
+        rest.insert(0, first);
+        op(rest)
+    }
+}
+
+fn parse_negation<'a, E>(i: &'a str) -> IResult<&'a str, QueryExpr, E>
+where
+    E: ParseError<&'a str> + ContextError<&'a str>,
+{
+    preceded(
+        multispace0,
+        alt((
+            map(preceded(char('!'), cut(parse_negation)), |expr| {
+                QueryExpr::Not(Box::new(expr))
+            }),
+            context(
+                "query or filter",
+                alt((
+                    map(parse_filter, QueryExpr::Filter),
+                    // Every part of a function query is optional, but an operand can't be empty.
+                    map(
+                        verify(consumed(query), |(consumed, _): &(&str, Query)| {
+                            !consumed.trim().is_empty()
+                        }),
+                        |(_, query)| QueryExpr::Query(query),
+                    ),
+                )),
+            ),
+        )),
+    )(i)
+}
+
+fn parse_filter<'a, E>(i: &'a str) -> IResult<&'a str, Filter, E>
+where
+    E: ParseError<&'a str> + ContextError<&'a str>,
+{
+    let kind = map_opt(parse_symbol, |kind| {
+        let kind = match kind.as_str() {
+            "fn" => ItemKind::Function,
+            "method" => ItemKind::Method,
+            "macro" => ItemKind::Macro,
+            "struct" => ItemKind::Struct,
+            "enum" => ItemKind::Enum,
+            "union" => ItemKind::Union,
+            "trait" => ItemKind::Trait,
+            "type" => ItemKind::TypeAlias,
+            _ => return None,
+        };
+        Some(kind)
+    });
+    let krate = map(
+        take_while1(|c: char| c.is_alphanumeric() || c == '_' || c == '-'),
+        |name: &str| name.to_string(),
+    );
+    let qualifier = alt((
+        value(Qualifier::Async, tag("async")),
+        value(Qualifier::Unsafe, tag("unsafe")),
+        value(Qualifier::Const, tag("const")),
+    ));
+    let deprecated = alt((
+        value(true, alt((tag("yes"), tag("true")))),
+        value(false, alt((tag("no"), tag("false")))),
+    ));
+
+    alt((
+        map(
+            preceded(tag("kind:"), cut(context("item kind", kind))),
+            Filter::Kind,
+        ),
+        map(
+            preceded(tag("crate:"), cut(context("crate name", krate))),
+            Filter::Crate,
+        ),
+        map(
+            preceded(tag("in:"), cut(context("module path", parse_path))),
+            Filter::In,
+        ),
+        map(
+            preceded(
+                tag("is:"),
+                cut(context("`async`, `unsafe` or `const`", qualifier)),
+            ),
+            Filter::Is,
+        ),
+        map(
+            preceded(
+                tag("deprecated:"),
+                cut(context("`yes` or `no`", deprecated)),
+            ),
+            Filter::Deprecated,
+        ),
+    ))(i)
+}
+
+fn chain_query<'a, E>(i: &'a str) -> IResult<&'a str, ChainQuery, E>
+where
+    E: ParseError<&'a str> + ContextError<&'a str>,
+{
+    preceded(
+        multispace0,
+        alt((
+            delimited(char('('), parse_chain, preceded(multispace0, char(')'))),
+            parse_chain,
+        )),
+    )(i)
+}
+
+fn parse_chain<'a, E>(i: &'a str) -> IResult<&'a str, ChainQuery, E>
+where
+    E: ParseError<&'a str> + ContextError<&'a str>,
+{
+    let arrow = |i| preceded(multispace0, tag("->"))(i);
+    let (i, from) = parse_type(i)?;
+    let (i, _) = arrow(i)?;
+    let (i, _) = preceded(multispace0, tag("..."))(i)?;
+    let (i, _) = arrow(i)?;
+    let (i, to) = parse_type(i)?;
+
+    Ok((i, ChainQuery { from, to }))
 }
 
 fn parse_symbol<'a, E>(i: &'a str) -> IResult<&'a str, Symbol, E>
@@ -31,6 +317,163 @@ where
     )(i)
 }
 
+/// Parses a lifetime, e.g. `'a`, `'static` or `'_`.
+fn parse_lifetime<'a, E>(i: &'a str) -> IResult<&'a str, Symbol, E>
+where
+    E: ParseError<&'a str> + ContextError<&'a str>,
+{
+    map(
+        recognize(pair(char('\''), parse_symbol)),
+        |lifetime: &str| lifetime.to_string(),
+    )(i)
+}
+
+/// Parses a const generic argument or array length, e.g. `32` or `{ N + 1 }`.
+fn parse_const<'a, E>(i: &'a str) -> IResult<&'a str, Symbol, E>
+where
+    E: ParseError<&'a str> + ContextError<&'a str>,
+{
+    map(
+        alt((
+            recognize(pair(digit1, many0(alt((tag("_"), alphanumeric1))))),
+            recognize(delimited(char('{'), take_while(|c| c != '}'), char('}'))),
+        )),
+        |expr: &str| expr.to_string(),
+    )(i)
+}
+
+/// Parses a possibly path-qualified symbol, e.g. `Error`, `io::Error` or `std::io::Error`.
+fn parse_path<'a, E>(i: &'a str) -> IResult<&'a str, Symbol, E>
+where
+    E: ParseError<&'a str> + ContextError<&'a str>,
+{
+    map(
+        recognize(separated_list1(tag("::"), parse_symbol)),
+        |path: &str| path.to_string(),
+    )(i)
+}
+
+fn parse_scope<'a, E>(i: &'a str) -> IResult<&'a str, QueryScope, E>
+where
+    E: ParseError<&'a str> + ContextError<&'a str>,
+{
+    let (i, kind) = preceded(
+        multispace0,
+        alt((
+            value(ScopeKind::Module, tag("mod")),
+            value(ScopeKind::Struct, tag("struct")),
+            value(ScopeKind::Enum, tag("enum")),
+            value(ScopeKind::Trait, tag("trait")),
+        )),
+    )(i)?;
+    let (i, name) = preceded(multispace1, parse_symbol)(i)?;
+    let (i, _) = preceded(multispace0, char(':'))(i)?;
+
+    Ok((i, QueryScope { kind, name }))
+}
+
+fn parse_macro_query<'a, E>(i: &'a str) -> IResult<&'a str, Query, E>
+where
+    E: ParseError<&'a str> + ContextError<&'a str>,
+{
+    let (i, _) = preceded(
+        multispace0,
+        terminated(tag("macro"), not(alt((alphanumeric1, tag("_"))))),
+    )(i)?;
+    let (i, kind) = opt(preceded(
+        multispace1,
+        alt((
+            value(
+                MacroKind::Derive,
+                terminated(tag("derive"), not(alt((alphanumeric1, tag("_"))))),
+            ),
+            value(
+                MacroKind::Attr,
+                terminated(tag("attr"), not(alt((alphanumeric1, tag("_"))))),
+            ),
+        )),
+    ))(i)?;
+    let (i, name) = opt(preceded(multispace1, parse_symbol))(i)?;
+    let (i, bang) = opt(char('!'))(i)?;
+    let (i, fragments) = opt(preceded(
+        multispace0,
+        delimited(
+            char('('),
+            separated_list0(char(','), preceded(multispace0, parse_symbol)),
+            preceded(multispace0, char(')')),
+        ),
+    ))(i)?;
+
+    let kind = kind.or(bang.map(|_| MacroKind::Bang));
+    let query = Query {
+        scope: None,
+        name,
+        kind: Some(QueryKind::MacroQuery(Macro { kind, fragments })),
+    };
+    Ok((i, query))
+}
+
+fn parse_type_def_query<'a, E>(i: &'a str) -> IResult<&'a str, Query, E>
+where
+    E: ParseError<&'a str> + ContextError<&'a str>,
+{
+    let (i, keyword) = preceded(
+        multispace0,
+        alt((
+            tag("struct"),
+            tag("enum"),
+            tag("union"),
+            tag("trait"),
+            tag("type"),
+        )),
+    )(i)?;
+    let (i, name) = preceded(multispace1, parse_symbol)(i)?;
+    let (i, params) = opt(delimited(
+        char('<'),
+        separated_list0(
+            char(','),
+            preceded(
+                multispace0,
+                alt((
+                    value((), char('_')),
+                    value((), parse_generic_param),
+                    value((), parse_type),
+                )),
+            ),
+        ),
+        preceded(multispace0, char('>')),
+    ))(i)?;
+    let (i, aliased) = if keyword == "type" {
+        opt(preceded(
+            preceded(multispace0, char('=')),
+            preceded(
+                multispace0,
+                alt((value(None, char('_')), map(parse_type, Some))),
+            ),
+        ))(i)?
+    } else {
+        (i, None)
+    };
+
+    let def = TypeDef {
+        arity: params.map(|params| params.len()),
+        aliased: aliased.flatten(),
+    };
+    let kind = match keyword {
+        "struct" => QueryKind::StructQuery(def),
+        "enum" => QueryKind::EnumQuery(def),
+        "union" => QueryKind::UnionQuery(def),
+        "trait" => QueryKind::TraitQuery(def),
+        _ => QueryKind::TypeAliasQuery(def),
+    };
+    let query = Query {
+        scope: None,
+        name: (name != "_").then_some(name),
+        kind: Some(kind),
+    };
+    Ok((i, query))
+}
+
 fn parse_function_query<'a, E>(i: &'a str) -> IResult<&'a str, Query, E>
 where
     E: ParseError<&'a str> + ContextError<&'a str>,
@@ -61,52 +504,237 @@ where
         })
         .collect::<HashSet<_>>();
 
-    let (i, name) = opt(preceded(multispace1, parse_symbol))(i)?;
+    // `Vec<u8>.len()` asks for a method of `Vec<u8>`, and `Vec<u8>.?()` for any of them.
+    let (i, receiver) = opt(backtrack(preceded(
+        multispace0,
+        terminated(parse_type, char('.')),
+    )))(i)?;
+    let (i, name) = match receiver {
+        Some(_) => alt((map(parse_path, Some), value(None, char('?'))))(i)?,
+        None => opt(preceded(multispace1, parse_path))(i)?,
+    };
     let (i, mut decl) = opt(preceded(multispace0, parse_function))(i)?;
 
+    if let Some(ty) = receiver {
+        let function = decl.get_or_insert_with(|| Function {
+            decl: FnDecl {
+                inputs: None,
+                output: None,
+                unordered: false,
+                receiver: None,
+            },
+            qualifiers: HashSet::new(),
+            generics: Generics::default(),
+        });
+        function
+            .decl
+            .receiver
+            .get_or_insert_with(|| {
+                Box::new(Receiver {
+                    mode: None,
+                    type_: None,
+                })
+            })
+            .type_ = Some(ty);
+    }
     if let Some(d) = decl.as_mut() {
         d.qualifiers = qualifiers;
     }
 
     let query = Query {
+        scope: None,
         name,
         kind: decl.map(QueryKind::FunctionQuery),
     };
     Ok((i, query))
 }
 
+/// Turns the failures `cut` raises inside `parser` back into errors, for when `parser` is only a
+/// guess at what comes next.
+fn backtrack<'a, O, E, F>(mut parser: F) -> impl FnMut(&'a str) -> IResult<&'a str, O, E>
+where
+    F: FnMut(&'a str) -> IResult<&'a str, O, E>,
+{
+    move |i| match parser(i) {
+        Err(nom::Err::Failure(e)) => Err(nom::Err::Error(e)),
+        result => result,
+    }
+}
+
 fn parse_function<'a, E>(i: &'a str) -> IResult<&'a str, Function, E>
 where
     E: ParseError<&'a str> + ContextError<&'a str>,
 {
-    let (i, decl) = parse_function_decl(i)?;
+    let (i, generics) = opt(parse_generics)(i)?;
+    let (i, decl) = preceded(multispace0, parse_function_decl)(i)?;
+    let (i, where_predicates) = opt(parse_where_clause)(i)?;
+
+    let mut generics = generics.unwrap_or_default();
+    generics.where_predicates = where_predicates.unwrap_or_default();
 
     let function = Function {
         decl,
         qualifiers: HashSet::new(),
+        generics,
     };
     Ok((i, function))
 }
 
+fn parse_generics<'a, E>(i: &'a str) -> IResult<&'a str, Generics, E>
+where
+    E: ParseError<&'a str> + ContextError<&'a str>,
+{
+    map(
+        delimited(
+            char('<'),
+            separated_list0(
+                char(','),
+                preceded(
+                    multispace0,
+                    alt((
+                        map(parse_generic_param, Some),
+                        // Lifetime parameters are not compared.
+                        value(
+                            None,
+                            pair(
+                                parse_lifetime,
+                                opt(preceded(preceded(multispace0, char(':')), parse_bounds)),
+                            ),
+                        ),
+                    )),
+                ),
+            ),
+            cut(preceded(multispace0, char('>'))),
+        ),
+        |params| Generics {
+            params: params.into_iter().flatten().collect(),
+            where_predicates: vec![],
+        },
+    )(i)
+}
+
+fn parse_generic_param<'a, E>(i: &'a str) -> IResult<&'a str, GenericParamDef, E>
+where
+    E: ParseError<&'a str> + ContextError<&'a str>,
+{
+    // The type of a const parameter only restates how it's used, so only its name is kept.
+    let const_param = preceded(
+        pair(tag("const"), multispace1),
+        terminated(
+            parse_symbol,
+            tuple((multispace0, char(':'), multispace0, parse_type)),
+        ),
+    );
+    let (i, name) = alt((const_param, parse_symbol))(i)?;
+    let (i, bounds) = opt(preceded(preceded(multispace0, char(':')), parse_bounds))(i)?;
+
+    let param = GenericParamDef {
+        name,
+        bounds: bounds.unwrap_or_default(),
+    };
+    Ok((i, param))
+}
+
+fn parse_bounds<'a, E>(i: &'a str) -> IResult<&'a str, Vec<GenericBound>, E>
+where
+    E: ParseError<&'a str> + ContextError<&'a str>,
+{
+    map(
+        separated_list1(
+            preceded(multispace0, char('+')),
+            preceded(
+                multispace0,
+                alt((
+                    map(parse_unresolved_path, |ty| {
+                        Some(GenericBound::TraitBound(ty))
+                    }),
+                    // Lifetime bounds are not compared.
+                    value(None, parse_lifetime),
+                )),
+            ),
+        ),
+        |bounds| bounds.into_iter().flatten().collect(),
+    )(i)
+}
+
 fn parse_function_decl<'a, E>(i: &'a str) -> IResult<&'a str, FnDecl, E>
 where
     E: ParseError<&'a str> + ContextError<&'a str>,
 {
-    let (i, inputs) = delimited(
-        char('('),
-        alt((
-            value(None, tag("..")),
-            opt(parse_arguments),
-            value(Some(Vec::new()), not(eof)),
-        )),
-        char(')'),
-    )(i)?;
+    let inputs = || {
+        pair(
+            opt(terminated(
+                preceded(multispace0, parse_receiver_mode),
+                opt(preceded(multispace0, char(','))),
+            )),
+            alt((
+                value(None, preceded(multispace0, tag(".."))),
+                opt(parse_arguments),
+                value(Some(Vec::new()), not(eof)),
+            )),
+        )
+    };
+    // Arguments in braces, e.g. `{usize, &str}`, may match in any order.
+    let (i, ((mode, inputs), unordered)) = alt((
+        map(
+            delimited(char('('), inputs(), cut(preceded(multispace0, char(')')))),
+            |inputs| (inputs, false),
+        ),
+        map(
+            delimited(char('{'), inputs(), cut(preceded(multispace0, char('}')))),
+            |inputs| (inputs, true),
+        ),
+    ))(i)?;
     let (i, output) = opt(parse_output)(i)?;
 
-    let decl = FnDecl { inputs, output };
+    let decl = FnDecl {
+        inputs,
+        output,
+        unordered,
+        receiver: mode.map(|mode| {
+            Box::new(Receiver {
+                mode: Some(mode),
+                type_: None,
+            })
+        }),
+    };
     Ok((i, decl))
 }
 
+/// Parses the `self`, `&self` or `&mut self` a method query starts its arguments with.
+fn parse_receiver_mode<'a, E>(i: &'a str) -> IResult<&'a str, ReceiverMode, E>
+where
+    E: ParseError<&'a str> + ContextError<&'a str>,
+{
+    let self_ = || verify(parse_symbol, |symbol: &str| symbol == "self");
+    alt((
+        value(
+            ReceiverMode::RefMut,
+            tuple((
+                char('&'),
+                opt(preceded(multispace0, parse_lifetime)),
+                multispace0,
+                tag("mut"),
+                multispace1,
+                self_(),
+            )),
+        ),
+        value(
+            ReceiverMode::Ref,
+            tuple((
+                char('&'),
+                opt(preceded(multispace0, parse_lifetime)),
+                multispace0,
+                self_(),
+            )),
+        ),
+        value(
+            ReceiverMode::Value,
+            pair(opt(pair(tag("mut"), multispace1)), self_()),
+        ),
+    ))(i)
+}
+
 fn parse_arguments<'a, E>(i: &'a str) -> IResult<&'a str, Vec<Argument>, E>
 where
     E: ParseError<&'a str> + ContextError<&'a str>,
@@ -133,52 +761,143 @@ where
     )(i)
 }
 
-fn parse_argument<'a, E>(i: &'a str) -> IResult<&'a str, Argument, E>
+fn parse_argument<'a, E>(i: &'a str) -> IResult<&'a str, Argument, E>
+where
+    E: ParseError<&'a str> + ContextError<&'a str>,
+{
+    let (i, name) = alt((value(None, char('_')), opt(parse_symbol)))(i)?;
+    // A `::` belongs to a path such as `Self::Item`, not to an argument name.
+    let (i, _) = terminated(char(':'), not(char(':')))(i)?;
+    let (i, _) = multispace0(i)?;
+    let (i, ty) = alt((value(None, char('_')), opt(parse_type)))(i)?;
+
+    let arg = Argument { ty, name };
+    Ok((i, arg))
+}
+
+fn parse_output<'a, E>(i: &'a str) -> IResult<&'a str, FnRetTy, E>
+where
+    E: ParseError<&'a str> + ContextError<&'a str>,
+{
+    preceded(
+        multispace0,
+        alt((
+            value(
+                FnRetTy::DefaultReturn,
+                preceded(preceded(tag("->"), multispace0), tag("()")),
+            ),
+            map(
+                preceded(tag("->"), cut(context("type", parse_type))),
+                FnRetTy::Return,
+            ),
+            value(FnRetTy::DefaultReturn, eof),
+        )),
+    )(i)
+}
+
+fn parse_type<'a, E>(i: &'a str) -> IResult<&'a str, Type, E>
+where
+    E: ParseError<&'a str> + ContextError<&'a str>,
+{
+    preceded(
+        multispace0,
+        alt((
+            parse_qualified_path,
+            parse_projection,
+            parse_impl_trait,
+            parse_dyn_trait,
+            parse_function_pointer,
+            map(parse_primitive_type, Type::Primitive),
+            parse_generic_type,
+            parse_unresolved_path,
+            parse_tuple,
+            parse_slice,
+            value(Type::Never, char('!')),
+            map(preceded(char('?'), parse_symbol), Type::Hole),
+            parse_raw_pointer,
+            parse_borrowed_ref,
+        )),
+    )(i)
+}
+
+/// Parses a fully qualified projection, e.g. `<T as Iterator>::Item`.
+fn parse_qualified_path<'a, E>(i: &'a str) -> IResult<&'a str, Type, E>
+where
+    E: ParseError<&'a str> + ContextError<&'a str>,
+{
+    let (i, self_type) = preceded(char('<'), parse_type)(i)?;
+    let (i, trait_) = preceded(
+        tuple((multispace1, tag("as"), multispace1)),
+        parse_unresolved_path,
+    )(i)?;
+    let (i, name) = preceded(tuple((multispace0, char('>'), tag("::"))), parse_symbol)(i)?;
+
+    Ok((
+        i,
+        Type::QualifiedPath {
+            name,
+            self_type: Box::new(self_type),
+            trait_: Some(Box::new(trait_)),
+        },
+    ))
+}
+
+/// Parses a projection out of a generic or `Self`, e.g. `T::Item`.
+fn parse_projection<'a, E>(i: &'a str) -> IResult<&'a str, Type, E>
+where
+    E: ParseError<&'a str> + ContextError<&'a str>,
+{
+    let self_path = map(terminated(tag("Self"), not(alphanumeric1)), |s: &str| {
+        Type::UnresolvedPath {
+            name: s.to_owned(),
+            args: None,
+        }
+    });
+    let (i, self_type) = alt((self_path, parse_generic_type))(i)?;
+    let (i, name) = preceded(tag("::"), parse_symbol)(i)?;
+
+    Ok((
+        i,
+        Type::QualifiedPath {
+            name,
+            self_type: Box::new(self_type),
+            trait_: None,
+        },
+    ))
+}
+
+fn parse_impl_trait<'a, E>(i: &'a str) -> IResult<&'a str, Type, E>
 where
     E: ParseError<&'a str> + ContextError<&'a str>,
 {
-    let (i, name) = alt((value(None, char('_')), opt(parse_symbol)))(i)?;
-    let (i, _) = char(':')(i)?;
-    let (i, _) = multispace0(i)?;
-    let (i, ty) = alt((value(None, char('_')), opt(parse_type)))(i)?;
-
-    let arg = Argument { ty, name };
-    Ok((i, arg))
+    map(
+        preceded(pair(tag("impl"), multispace1), parse_bounds),
+        Type::ImplTrait,
+    )(i)
 }
 
-fn parse_output<'a, E>(i: &'a str) -> IResult<&'a str, FnRetTy, E>
+fn parse_dyn_trait<'a, E>(i: &'a str) -> IResult<&'a str, Type, E>
 where
     E: ParseError<&'a str> + ContextError<&'a str>,
 {
-    preceded(
-        multispace0,
-        alt((
-            value(
-                FnRetTy::DefaultReturn,
-                preceded(preceded(tag("->"), multispace0), tag("()")),
-            ),
-            map(preceded(tag("->"), parse_type), FnRetTy::Return),
-            value(FnRetTy::DefaultReturn, eof),
-        )),
+    map(
+        preceded(pair(tag("dyn"), multispace1), parse_bounds),
+        Type::DynTrait,
     )(i)
 }
 
-fn parse_type<'a, E>(i: &'a str) -> IResult<&'a str, Type, E>
+fn parse_function_pointer<'a, E>(i: &'a str) -> IResult<&'a str, Type, E>
 where
     E: ParseError<&'a str> + ContextError<&'a str>,
 {
-    preceded(
-        multispace0,
-        alt((
-            map(parse_primitive_type, Type::Primitive),
-            parse_generic_type,
-            parse_unresolved_path,
-            parse_tuple,
-            parse_slice,
-            value(Type::Never, char('!')),
-            parse_raw_pointer,
-            parse_borrowed_ref,
-        )),
+    map(
+        preceded(
+            pair(tag("fn"), multispace0),
+            verify(parse_function_decl, |decl: &FnDecl| {
+                !decl.unordered && decl.receiver.is_none()
+            }),
+        ),
+        |decl| Type::FunctionPointer(Box::new(decl)),
     )(i)
 }
 
@@ -206,14 +925,31 @@ fn parse_slice<'a, E>(i: &'a str) -> IResult<&'a str, Type, E>
 where
     E: ParseError<&'a str> + ContextError<&'a str>,
 {
-    map(
-        delimited(
-            char('['),
-            alt((value(None, tag("_")), map(parse_type, Some))),
-            char(']'),
+    let (i, ty) = preceded(
+        char('['),
+        alt((
+            value(None, preceded(multispace0, tag("_"))),
+            map(parse_type, Some),
+        )),
+    )(i)?;
+    let (i, len) = opt(preceded(
+        pair(multispace0, char(';')),
+        preceded(
+            multispace0,
+            alt((
+                value(None, tag("_")),
+                map(alt((parse_const, parse_symbol)), Some),
+            )),
         ),
-        |ty| Type::Slice(ty.map(Box::new)),
-    )(i)
+    ))(i)?;
+    let (i, _) = preceded(multispace0, char(']'))(i)?;
+
+    let type_ = ty.map(Box::new);
+    let ty = match len {
+        Some(len) => Type::Array { type_, len },
+        None => Type::Slice(type_),
+    };
+    Ok((i, ty))
 }
 
 fn parse_raw_pointer<'a, E>(i: &'a str) -> IResult<&'a str, Type, E>
@@ -236,7 +972,16 @@ fn parse_borrowed_ref<'a, E>(i: &'a str) -> IResult<&'a str, Type, E>
 where
     E: ParseError<&'a str> + ContextError<&'a str>,
 {
-    let (i, mutable) = alt((value(true, tag("&mut")), value(false, tag("&"))))(i)?;
+    let (i, _) = char('&')(i)?;
+    // Lifetimes are not compared.
+    let (i, _) = opt(preceded(multispace0, parse_lifetime))(i)?;
+    let (i, mutable) = map(
+        opt(preceded(
+            multispace0,
+            terminated(tag("mut"), not(alphanumeric1)),
+        )),
+        |mutable| mutable.is_some(),
+    )(i)?;
     let (i, type_) = parse_type(i)?;
 
     Ok((
@@ -252,8 +997,8 @@ fn parse_unresolved_path<'a, E>(i: &'a str) -> IResult<&'a str, Type, E>
 where
     E: ParseError<&'a str> + ContextError<&'a str>,
 {
-    let (i, name) = parse_symbol(i)?;
-    let (i, args) = opt(parse_generic_args)(i)?;
+    let (i, name) = parse_path(i)?;
+    let (i, args) = opt(alt((parse_generic_args, parse_parenthesized_args)))(i)?;
 
     Ok((
         i,
@@ -264,6 +1009,12 @@ where
     ))
 }
 
+#[derive(Clone)]
+enum GenericArgEntry {
+    Arg(Option<GenericArg>),
+    Constraint(AssocItemConstraint),
+}
+
 fn parse_generic_args<'a, E>(i: &'a str) -> IResult<&'a str, GenericArgs, E>
 where
     E: ParseError<&'a str> + ContextError<&'a str>,
@@ -276,17 +1027,87 @@ where
                 preceded(
                     multispace0,
                     alt((
-                        value(None, tag("_")),
-                        opt(map(parse_type, GenericArg::Type)),
+                        map(parse_assoc_item_constraint, GenericArgEntry::Constraint),
+                        value(GenericArgEntry::Arg(None), tag("_")),
+                        map(parse_lifetime, |l| {
+                            GenericArgEntry::Arg(Some(GenericArg::Lifetime(l)))
+                        }),
+                        map(parse_const, |c| {
+                            GenericArgEntry::Arg(Some(GenericArg::Const(c)))
+                        }),
+                        map(opt(map(parse_type, GenericArg::Type)), GenericArgEntry::Arg),
                     )),
                 ),
             ),
-            char('>'),
+            cut(preceded(multispace0, char('>'))),
         ),
-        |args| GenericArgs::AngleBracketed { args },
+        |entries| {
+            let mut args = vec![];
+            let mut constraints = vec![];
+            for entry in entries {
+                match entry {
+                    GenericArgEntry::Arg(arg) => args.push(arg),
+                    GenericArgEntry::Constraint(constraint) => constraints.push(constraint),
+                }
+            }
+            GenericArgs::AngleBracketed { args, constraints }
+        },
     )(i)
 }
 
+/// Parses the arguments of the `Fn*` traits, e.g. `(&T) -> bool` in `FnMut(&T) -> bool`.
+fn parse_parenthesized_args<'a, E>(i: &'a str) -> IResult<&'a str, GenericArgs, E>
+where
+    E: ParseError<&'a str> + ContextError<&'a str>,
+{
+    let (i, inputs) = delimited(
+        char('('),
+        separated_list0(char(','), parse_type),
+        preceded(multispace0, char(')')),
+    )(i)?;
+    let (i, output) = opt(preceded(pair(multispace0, tag("->")), parse_type))(i)?;
+
+    Ok((i, GenericArgs::Parenthesized { inputs, output }))
+}
+
+fn parse_assoc_item_constraint<'a, E>(i: &'a str) -> IResult<&'a str, AssocItemConstraint, E>
+where
+    E: ParseError<&'a str> + ContextError<&'a str>,
+{
+    let (i, name) = parse_symbol(i)?;
+    let (i, _) = preceded(multispace0, char('='))(i)?;
+    let (i, term) = parse_type(i)?;
+
+    Ok((i, AssocItemConstraint { name, term }))
+}
+
+fn parse_where_clause<'a, E>(i: &'a str) -> IResult<&'a str, Vec<WherePredicate>, E>
+where
+    E: ParseError<&'a str> + ContextError<&'a str>,
+{
+    preceded(
+        preceded(multispace0, tag("where")),
+        cut(separated_list1(
+            preceded(multispace0, char(',')),
+            preceded(
+                multispace1,
+                context("where predicate", parse_where_predicate),
+            ),
+        )),
+    )(i)
+}
+
+fn parse_where_predicate<'a, E>(i: &'a str) -> IResult<&'a str, WherePredicate, E>
+where
+    E: ParseError<&'a str> + ContextError<&'a str>,
+{
+    let (i, type_) = parse_type(i)?;
+    let (i, _) = preceded(multispace0, char(':'))(i)?;
+    let (i, bounds) = parse_bounds(i)?;
+
+    Ok((i, WherePredicate::BoundPredicate { type_, bounds }))
+}
+
 fn parse_generic_type<'a, E>(i: &'a str) -> IResult<&'a str, Type, E>
 where
     E: ParseError<&'a str> + ContextError<&'a str>,
@@ -343,7 +1164,8 @@ mod tests {
                 type_: Box::new(Type::Slice(Some(Box::new(Type::UnresolvedPath {
                     name: "Option".to_string(),
                     args: Some(Box::new(GenericArgs::AngleBracketed {
-                        args: vec![Some(GenericArg::Type(Type::Primitive(PrimitiveType::I32)))]
+                        args: vec![Some(GenericArg::Type(Type::Primitive(PrimitiveType::I32)))],
+                        constraints: vec![],
                     }))
                 }))))
             }
@@ -379,7 +1201,8 @@ mod tests {
             Type::UnresolvedPath {
                 name: "Result".to_string(),
                 args: Some(Box::new(GenericArgs::AngleBracketed {
-                    args: vec![None, Some(GenericArg::Type(Type::Generic("E".to_string()))),]
+                    args: vec![None, Some(GenericArg::Type(Type::Generic("E".to_string()))),],
+                    constraints: vec![],
                 }))
             }
         );
@@ -406,6 +1229,8 @@ mod tests {
                     },
                 ]),
                 output: Some(FnRetTy::Return(Type::Primitive(PrimitiveType::Bool))),
+                unordered: false,
+                receiver: None,
             }
         );
     }
@@ -431,6 +1256,8 @@ mod tests {
                     },
                 ]),
                 output: Some(FnRetTy::DefaultReturn),
+                unordered: false,
+                receiver: None,
             }
         );
     }
@@ -454,6 +1281,8 @@ mod tests {
                     }),
                     Some(Type::Generic("T".to_string())),
                 ]))),
+                unordered: false,
+                receiver: None,
             }
         );
     }
@@ -465,6 +1294,7 @@ mod tests {
         assert_eq!(
             decl,
             Query {
+                scope: None,
                 name: Some("abc".to_string()),
                 kind: Some(QueryKind::FunctionQuery(Function {
                     decl: FnDecl {
@@ -477,13 +1307,18 @@ mod tests {
                                     args: Some(Box::new(GenericArgs::AngleBracketed {
                                         args: vec![Some(GenericArg::Type(Type::Primitive(
                                             PrimitiveType::I32
-                                        )))]
+                                        )))],
+                                        constraints: vec![],
                                     }))
-                                }))]
+                                }))],
+                                constraints: vec![],
                             }))
                         })),
+                        unordered: false,
+                        receiver: None,
                     },
                     qualifiers: HashSet::new(),
+                    generics: Generics::default(),
                 })),
             }
         );
@@ -496,6 +1331,7 @@ mod tests {
         assert_eq!(
             query,
             Query {
+                scope: None,
                 name: Some("foo".to_string()),
                 kind: Some(QueryKind::FunctionQuery(Function {
                     decl: FnDecl {
@@ -513,10 +1349,625 @@ mod tests {
                             },
                         ]),
                         output: Some(FnRetTy::Return(Type::Primitive(PrimitiveType::Bool))),
+                        unordered: false,
+                        receiver: None,
                     },
                     qualifiers: HashSet::from_iter(vec![Qualifier::Async]),
+                    generics: Generics::default(),
+                })),
+            }
+        );
+    }
+
+    #[test]
+    fn test_parse_bounded_generics() {
+        let input = "fn <K: Hash + Eq, V>(HashMap<K, V>, K) -> Option<V>";
+        let (_, query) = parse_query(input).unwrap();
+        let Some(QueryKind::FunctionQuery(function)) = query.kind else {
+            panic!("expected a function query");
+        };
+        let bound = |name: &str| {
+            GenericBound::TraitBound(Type::UnresolvedPath {
+                name: name.to_string(),
+                args: None,
+            })
+        };
+        assert_eq!(
+            function.generics,
+            Generics {
+                params: vec![
+                    GenericParamDef {
+                        name: "K".to_string(),
+                        bounds: vec![bound("Hash"), bound("Eq")],
+                    },
+                    GenericParamDef {
+                        name: "V".to_string(),
+                        bounds: vec![],
+                    },
+                ],
+                where_predicates: vec![],
+            }
+        );
+        assert_eq!(
+            function.decl.output,
+            Some(FnRetTy::Return(Type::UnresolvedPath {
+                name: "Option".to_string(),
+                args: Some(Box::new(GenericArgs::AngleBracketed {
+                    args: vec![Some(GenericArg::Type(Type::Generic("V".to_string())))],
+                    constraints: vec![],
+                }))
+            }))
+        );
+    }
+
+    #[test]
+    fn test_parse_where_clause() {
+        let input = "fn (I) -> Vec<T> where I: IntoIterator<Item = T>, T: Clone";
+        let (rest, query) = parse_query(input).unwrap();
+        assert_eq!(rest, "");
+        let Some(QueryKind::FunctionQuery(function)) = query.kind else {
+            panic!("expected a function query");
+        };
+        assert_eq!(
+            function.generics.where_predicates,
+            vec![
+                WherePredicate::BoundPredicate {
+                    type_: Type::Generic("I".to_string()),
+                    bounds: vec![GenericBound::TraitBound(Type::UnresolvedPath {
+                        name: "IntoIterator".to_string(),
+                        args: Some(Box::new(GenericArgs::AngleBracketed {
+                            args: vec![],
+                            constraints: vec![AssocItemConstraint {
+                                name: "Item".to_string(),
+                                term: Type::Generic("T".to_string()),
+                            }],
+                        })),
+                    })],
+                },
+                WherePredicate::BoundPredicate {
+                    type_: Type::Generic("T".to_string()),
+                    bounds: vec![GenericBound::TraitBound(Type::UnresolvedPath {
+                        name: "Clone".to_string(),
+                        args: None,
+                    })],
+                },
+            ]
+        );
+    }
+
+    #[test]
+    fn test_parse_chain_query() {
+        let expected = ChainQuery {
+            from: Type::UnresolvedPath {
+                name: "String".to_string(),
+                args: None,
+            },
+            to: Type::Primitive(PrimitiveType::Usize),
+        };
+        for input in ["String -> ... -> usize", "(String -> ... -> usize)"] {
+            let (rest, query) = parse_chain_query(input).unwrap();
+            assert_eq!(rest, "");
+            assert_eq!(query, expected);
+        }
+    }
+
+    #[test]
+    fn test_parse_scoped_query() {
+        let input = "struct Vec: fn(&Vec<T>) -> usize";
+        let (rest, query) = parse_query(input).unwrap();
+        assert_eq!(rest, "");
+        assert_eq!(
+            query.scope,
+            Some(QueryScope {
+                kind: ScopeKind::Struct,
+                name: "Vec".to_string(),
+            })
+        );
+        assert!(matches!(query.kind, Some(QueryKind::FunctionQuery(_))));
+
+        let (_, query) = parse_query("mod text: fn split_words").unwrap();
+        assert_eq!(
+            query.scope,
+            Some(QueryScope {
+                kind: ScopeKind::Module,
+                name: "text".to_string(),
+            })
+        );
+        assert_eq!(query.name, Some("split_words".to_string()));
+    }
+
+    #[test]
+    fn test_parse_macro_query() {
+        let (_, query) = parse_query("macro make_vec!").unwrap();
+        assert_eq!(query.name, Some("make_vec".to_string()));
+        assert_eq!(
+            query.kind,
+            Some(QueryKind::MacroQuery(Macro {
+                kind: Some(MacroKind::Bang),
+                fragments: None,
+            }))
+        );
+
+        let (_, query) = parse_query("macro (ident, ty)").unwrap();
+        assert_eq!(query.name, None);
+        assert_eq!(
+            query.kind,
+            Some(QueryKind::MacroQuery(Macro {
+                kind: None,
+                fragments: Some(vec!["ident".to_string(), "ty".to_string()]),
+            }))
+        );
+
+        let (_, query) = parse_query("macro derive Serialize").unwrap();
+        assert_eq!(query.name, Some("Serialize".to_string()));
+        assert_eq!(
+            query.kind,
+            Some(QueryKind::MacroQuery(Macro {
+                kind: Some(MacroKind::Derive),
+                fragments: None,
+            }))
+        );
+
+        // Names starting with a kind keyword are names, not kinds.
+        let query = parse_query_strict("macro derive_more").unwrap();
+        assert_eq!(query.name, Some("derive_more".to_string()));
+        assert_eq!(
+            query.kind,
+            Some(QueryKind::MacroQuery(Macro {
+                kind: None,
+                fragments: None,
+            }))
+        );
+        let query = parse_query_strict("macro attribute_x").unwrap();
+        assert_eq!(query.name, Some("attribute_x".to_string()));
+    }
+
+    #[test]
+    fn test_parse_type_def_query() {
+        let (_, query) = parse_query("struct Wrapper<_>").unwrap();
+        assert_eq!(query.name, Some("Wrapper".to_string()));
+        assert_eq!(
+            query.kind,
+            Some(QueryKind::StructQuery(TypeDef {
+                arity: Some(1),
+                aliased: None,
+            }))
+        );
+
+        let (_, query) = parse_query("trait Repository").unwrap();
+        assert_eq!(
+            query.kind,
+            Some(QueryKind::TraitQuery(TypeDef {
+                arity: None,
+                aliased: None,
+            }))
+        );
+
+        let (_, query) = parse_query("type _ = u64").unwrap();
+        assert_eq!(query.name, None);
+        assert_eq!(
+            query.kind,
+            Some(QueryKind::TypeAliasQuery(TypeDef {
+                arity: None,
+                aliased: Some(Type::Primitive(PrimitiveType::U64)),
+            }))
+        );
+
+        // A trailing `:` still makes a scoped function query.
+        let (_, query) = parse_query("struct Vec: fn(..) -> usize").unwrap();
+        assert!(query.scope.is_some());
+        assert!(matches!(query.kind, Some(QueryKind::FunctionQuery(_))));
+    }
+
+    #[test]
+    fn test_parse_impl_and_dyn_trait() {
+        let input = "fn (Box<dyn Error + Send>) -> impl Iterator<Item = u8>";
+        let (rest, query) = parse_query(input).unwrap();
+        assert_eq!(rest, "");
+        let Some(QueryKind::FunctionQuery(function)) = query.kind else {
+            panic!("expected a function query");
+        };
+        let path = |name: &str| {
+            GenericBound::TraitBound(Type::UnresolvedPath {
+                name: name.to_string(),
+                args: None,
+            })
+        };
+        assert_eq!(
+            function.decl.inputs,
+            Some(vec![Argument {
+                ty: Some(Type::UnresolvedPath {
+                    name: "Box".to_string(),
+                    args: Some(Box::new(GenericArgs::AngleBracketed {
+                        args: vec![Some(GenericArg::Type(Type::DynTrait(vec![
+                            path("Error"),
+                            path("Send"),
+                        ])))],
+                        constraints: vec![],
+                    })),
+                }),
+                name: None,
+            }])
+        );
+        assert_eq!(
+            function.decl.output,
+            Some(FnRetTy::Return(Type::ImplTrait(vec![
+                GenericBound::TraitBound(Type::UnresolvedPath {
+                    name: "Iterator".to_string(),
+                    args: Some(Box::new(GenericArgs::AngleBracketed {
+                        args: vec![],
+                        constraints: vec![AssocItemConstraint {
+                            name: "Item".to_string(),
+                            term: Type::Primitive(PrimitiveType::U8),
+                        }],
+                    })),
+                })
+            ])))
+        );
+
+        // Names merely starting with a keyword are still paths.
+        let (_, ty) = parse_type::<nom::error::VerboseError<&str>>("implementor").unwrap();
+        assert!(matches!(ty, Type::UnresolvedPath { .. }));
+    }
+
+    #[test]
+    fn test_parse_closures_and_function_pointers() {
+        let input = "fn (Vec<T>, F) -> Vec<T> where F: FnMut(&T) -> bool";
+        let (rest, query) = parse_query(input).unwrap();
+        assert_eq!(rest, "");
+        let Some(QueryKind::FunctionQuery(function)) = query.kind else {
+            panic!("expected a function query");
+        };
+        assert_eq!(
+            function.generics.where_predicates,
+            vec![WherePredicate::BoundPredicate {
+                type_: Type::Generic("F".to_string()),
+                bounds: vec![GenericBound::TraitBound(Type::UnresolvedPath {
+                    name: "FnMut".to_string(),
+                    args: Some(Box::new(GenericArgs::Parenthesized {
+                        inputs: vec![Type::BorrowedRef {
+                            mutable: false,
+                            type_: Box::new(Type::Generic("T".to_string())),
+                        }],
+                        output: Some(Type::Primitive(PrimitiveType::Bool)),
+                    })),
+                })],
+            }]
+        );
+
+        let (rest, query) = parse_query("fn (fn(i32) -> i32, i32) -> i32").unwrap();
+        assert_eq!(rest, "");
+        let Some(QueryKind::FunctionQuery(function)) = query.kind else {
+            panic!("expected a function query");
+        };
+        assert_eq!(
+            function.decl.inputs.unwrap()[0].ty,
+            Some(Type::FunctionPointer(Box::new(FnDecl {
+                inputs: Some(vec![Argument {
+                    ty: Some(Type::Primitive(PrimitiveType::I32)),
+                    name: None,
+                }]),
+                output: Some(FnRetTy::Return(Type::Primitive(PrimitiveType::I32))),
+                unordered: false,
+                receiver: None,
+            })))
+        );
+    }
+
+    #[test]
+    fn test_parse_projections() {
+        let projection = |self_type: Type, trait_: Option<&str>| Type::QualifiedPath {
+            name: "Item".to_string(),
+            self_type: Box::new(self_type),
+            trait_: trait_.map(|name| {
+                Box::new(Type::UnresolvedPath {
+                    name: name.to_string(),
+                    args: None,
+                })
+            }),
+        };
+        let generic = || Type::Generic("I".to_string());
+        let self_ = || Type::UnresolvedPath {
+            name: "Self".to_string(),
+            args: None,
+        };
+
+        for (input, expected) in [
+            ("I::Item", projection(generic(), None)),
+            ("Self::Item", projection(self_(), None)),
+            (
+                "<I as Iterator>::Item",
+                projection(generic(), Some("Iterator")),
+            ),
+            (
+                "< Self as Iterator >::Item",
+                projection(self_(), Some("Iterator")),
+            ),
+        ] {
+            let (rest, ty) = parse_type::<nom::error::VerboseError<&str>>(input).unwrap();
+            assert_eq!(rest, "", "{input}");
+            assert_eq!(ty, expected, "{input}");
+        }
+
+        let (rest, query) = parse_query("fn <I: Iterator>(I) -> Option<I::Item>").unwrap();
+        assert_eq!(rest, "");
+        let Some(QueryKind::FunctionQuery(function)) = query.kind else {
+            panic!("expected a function query");
+        };
+        assert_eq!(
+            function.decl.output,
+            Some(FnRetTy::Return(Type::UnresolvedPath {
+                name: "Option".to_string(),
+                args: Some(Box::new(GenericArgs::AngleBracketed {
+                    args: vec![Some(GenericArg::Type(projection(generic(), None)))],
+                    constraints: vec![],
+                })),
+            }))
+        );
+    }
+
+    #[test]
+    fn test_parse_qualified_paths() {
+        let (rest, query) =
+            parse_query("fn io::read_to_string(&mut R) -> io::Result<String>").unwrap();
+        assert_eq!(rest, "");
+        assert_eq!(query.name, Some("io::read_to_string".to_string()));
+        let Some(QueryKind::FunctionQuery(function)) = query.kind else {
+            panic!("expected a function query");
+        };
+        assert_eq!(
+            function.decl.output,
+            Some(FnRetTy::Return(Type::UnresolvedPath {
+                name: "io::Result".to_string(),
+                args: Some(Box::new(GenericArgs::AngleBracketed {
+                    args: vec![Some(GenericArg::Type(Type::UnresolvedPath {
+                        name: "String".to_string(),
+                        args: None,
+                    }))],
+                    constraints: vec![],
+                })),
+            }))
+        );
+
+        // Named arguments are still told apart from qualified types.
+        let (rest, query) = parse_query("fn (e: std::fmt::Error)").unwrap();
+        assert_eq!(rest, "");
+        let Some(QueryKind::FunctionQuery(function)) = query.kind else {
+            panic!("expected a function query");
+        };
+        assert_eq!(
+            function.decl.inputs,
+            Some(vec![Argument {
+                ty: Some(Type::UnresolvedPath {
+                    name: "std::fmt::Error".to_string(),
+                    args: None,
+                }),
+                name: Some("e".to_string()),
+            }])
+        );
+    }
+
+    #[test]
+    fn test_parse_lifetimes_arrays_and_consts() {
+        let input = "fn <'a, T: 'a + Clone, const N: usize>(&'a mut BoundedVec<'a, T, N>, [u8; 32]) -> [T; _]";
+        let (rest, query) = parse_query(input).unwrap();
+        assert_eq!(rest, "");
+        let Some(QueryKind::FunctionQuery(function)) = query.kind else {
+            panic!("expected a function query");
+        };
+        assert_eq!(
+            function.generics.params,
+            vec![
+                GenericParamDef {
+                    name: "T".to_string(),
+                    bounds: vec![GenericBound::TraitBound(Type::UnresolvedPath {
+                        name: "Clone".to_string(),
+                        args: None,
+                    })],
+                },
+                GenericParamDef {
+                    name: "N".to_string(),
+                    bounds: vec![],
+                },
+            ]
+        );
+        assert_eq!(
+            function.decl.inputs,
+            Some(vec![
+                Argument {
+                    ty: Some(Type::BorrowedRef {
+                        mutable: true,
+                        type_: Box::new(Type::UnresolvedPath {
+                            name: "BoundedVec".to_string(),
+                            args: Some(Box::new(GenericArgs::AngleBracketed {
+                                args: vec![
+                                    Some(GenericArg::Lifetime("'a".to_string())),
+                                    Some(GenericArg::Type(Type::Generic("T".to_string()))),
+                                    Some(GenericArg::Type(Type::Generic("N".to_string()))),
+                                ],
+                                constraints: vec![],
+                            })),
+                        }),
+                    }),
+                    name: None,
+                },
+                Argument {
+                    ty: Some(Type::Array {
+                        type_: Some(Box::new(Type::Primitive(PrimitiveType::U8))),
+                        len: Some("32".to_string()),
+                    }),
+                    name: None,
+                },
+            ])
+        );
+        assert_eq!(
+            function.decl.output,
+            Some(FnRetTy::Return(Type::Array {
+                type_: Some(Box::new(Type::Generic("T".to_string()))),
+                len: None,
+            }))
+        );
+
+        let (_, ty) = parse_type::<nom::error::VerboseError<&str>>("Wrapper<{ N + 1 }>").unwrap();
+        assert_eq!(
+            ty,
+            Type::UnresolvedPath {
+                name: "Wrapper".to_string(),
+                args: Some(Box::new(GenericArgs::AngleBracketed {
+                    args: vec![Some(GenericArg::Const("{ N + 1 }".to_string()))],
+                    constraints: vec![],
                 })),
             }
         );
     }
+
+    #[test]
+    fn test_parse_query_strict() {
+        assert!(parse_query_strict("fn (Vec<u8>) -> usize ").is_ok());
+        assert!(parse_chain_query_strict("(String -> ... -> usize)").is_ok());
+
+        let err = parse_query_strict("fn (Vec<u8>) -> ").unwrap_err();
+        assert_eq!(err.offset, 16);
+        assert_eq!(err.expected, vec!["type".to_string()]);
+        assert_eq!(
+            err.to_string(),
+            "parsing query failed at byte 16, expected type\n  fn (Vec<u8>) -> \n                  ^"
+        );
+
+        let err = parse_query_strict("fn (Vec<u8, usize) -> u8").unwrap_err();
+        assert_eq!(err.offset, 17);
+        assert!(err.expected.contains(&"`>`".to_string()));
+
+        // `parse_query` stops at the garbage, the strict variant points at it.
+        assert!(parse_query("fn (u8) -> u8 garbage").is_ok());
+        let err = parse_query_strict("fn (u8) -> u8 garbage").unwrap_err();
+        assert_eq!(err.offset, 14);
+        assert!(err.expected.contains(&"end of query".to_string()));
+    }
+
+    #[test]
+    fn test_parse_query_expr() {
+        let (rest, expr) =
+            parse_query_expr("fn () -> Option<_> | fn () -> Result<_, _> & !is:unsafe").unwrap();
+        assert_eq!(rest, "");
+        let QueryExpr::Or(alternatives) = &expr else {
+            panic!("expected an alternation, got {:?}", expr);
+        };
+        assert!(matches!(alternatives[0], QueryExpr::Query(_)));
+        assert!(matches!(
+            &alternatives[1],
+            QueryExpr::And(operands)
+                if matches!(operands[1], QueryExpr::Not(ref f)
+                    if **f == QueryExpr::Filter(Filter::Is(Qualifier::Unsafe)))
+        ));
+        assert_eq!(
+            parse_query_expr_strict("fn parse | fn (u8) -> u8 & !is:unsafe")
+                .unwrap()
+                .to_string(),
+            "fn parse | fn(u8) -> u8 & !is:unsafe"
+        );
+
+        let expr = parse_query_expr_strict(
+            "kind:method & crate:serde-json & in:collections::hash_map & deprecated:no",
+        )
+        .unwrap();
+        assert_eq!(
+            expr,
+            QueryExpr::And(vec![
+                QueryExpr::Filter(Filter::Kind(ItemKind::Method)),
+                QueryExpr::Filter(Filter::Crate("serde-json".to_string())),
+                QueryExpr::Filter(Filter::In("collections::hash_map".to_string())),
+                QueryExpr::Filter(Filter::Deprecated(false)),
+            ])
+        );
+
+        // References and the never type are still types.
+        let (rest, expr) = parse_query_expr("fn (&str) -> ! & is:const").unwrap();
+        assert_eq!(rest, "");
+        assert!(matches!(expr, QueryExpr::And(ref operands) if operands.len() == 2));
+
+        let err = parse_query_expr_strict("fn parse | ").unwrap_err();
+        assert_eq!(err.expected, vec!["query or filter".to_string()]);
+        let err = parse_query_expr_strict("kind:function").unwrap_err();
+        assert_eq!(err.offset, 5);
+        assert_eq!(err.expected, vec!["item kind".to_string()]);
+    }
+
+    #[test]
+    fn test_parse_named_holes() {
+        let query = parse_query_strict("fn (Vec<?a>, ?a) -> Option<?elem>").unwrap();
+        let hole = |name: &str| Some(Type::Hole(name.to_string()));
+        let args = query.args().unwrap();
+        assert_eq!(
+            args[0].ty,
+            Some(Type::UnresolvedPath {
+                name: "Vec".to_string(),
+                args: Some(Box::new(GenericArgs::AngleBracketed {
+                    args: vec![hole("a").map(GenericArg::Type)],
+                    constraints: vec![],
+                })),
+            })
+        );
+        assert_eq!(args[1].ty, hole("a"));
+        assert_eq!(query.to_string(), "fn(Vec<?a>, ?a) -> Option<?elem>");
+    }
+
+    #[test]
+    fn test_parse_unordered_arguments() {
+        let query = parse_query_strict("fn {usize, &str} -> bool").unwrap();
+        let Some(QueryKind::FunctionQuery(ref function)) = query.kind else {
+            panic!("expected a function query, got {:?}", query);
+        };
+        assert!(function.decl.unordered);
+        assert_eq!(query.args().unwrap().len(), 2);
+        assert_eq!(query.to_string(), "fn{usize, &str} -> bool");
+
+        // Function pointers keep their arguments in order.
+        assert!(parse_query_strict("fn (fn{usize}) -> bool").is_err());
+    }
+
+    #[test]
+    fn test_parse_receivers() {
+        let receiver = |q: &str| {
+            let query = parse_query_strict(q).unwrap();
+            let Some(QueryKind::FunctionQuery(function)) = query.kind else {
+                panic!("expected a function query, got {:?}", query);
+            };
+            (query.name, function.decl.receiver, function.decl.inputs)
+        };
+
+        let (_, r, inputs) = receiver("fn (&mut self, T) -> bool");
+        assert_eq!(r.unwrap().mode, Some(ReceiverMode::RefMut));
+        assert_eq!(inputs.unwrap().len(), 1);
+        let (_, r, inputs) = receiver("fn (&'a self)");
+        assert_eq!(r.unwrap().mode, Some(ReceiverMode::Ref));
+        assert_eq!(inputs, Some(vec![]));
+        let (_, r, inputs) = receiver("fn into_inner(self, ..)");
+        assert_eq!(r.unwrap().mode, Some(ReceiverMode::Value));
+        assert_eq!(inputs, None);
+        // Only `self` itself is a receiver.
+        let (_, r, _) = receiver("fn (selfish: u8)");
+        assert_eq!(r, None);
+
+        let (name, r, inputs) = receiver("Vec<u8>.?(..) -> String");
+        let r = r.unwrap();
+        assert_eq!(name, None);
+        assert_eq!(r.mode, None);
+        assert_eq!(r.type_.unwrap().to_string(), "Vec<u8>");
+        assert_eq!(inputs, None);
+        let (name, r, _) = receiver("fn BoundedVec<T, N>.push(&mut self, T)");
+        assert_eq!(name.as_deref(), Some("push"));
+        let r = r.unwrap();
+        assert_eq!(r.mode, Some(ReceiverMode::RefMut));
+        assert_eq!(r.type_.unwrap().to_string(), "BoundedVec<T, N>");
+
+        // A type that isn't followed by `.` is still a function name.
+        let (name, r, _) = receiver("fn foo<T: Clone>(T)");
+        assert_eq!(name.as_deref(), Some("foo"));
+        assert_eq!(r, None);
+
+        let query = parse_query_strict("Vec<u8>.len(&self) -> usize").unwrap();
+        assert_eq!(query.to_string(), "fn Vec<u8>.len(&self) -> usize");
+        assert_eq!(parse_query_strict(&query.to_string()).unwrap(), query);
+    }
 }
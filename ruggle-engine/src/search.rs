@@ -1,8 +1,8 @@
-use std::collections::HashMap;
+use std::collections::{BTreeMap, HashMap};
 
 use crate::{
     reconstruct_path_for_local,
-    types::{self, CrateMetadata, GenericArgs, ItemEnum},
+    types::{self, CrateMetadata, ItemEnum},
     Parent,
 };
 
@@ -10,8 +10,12 @@ use serde::{Deserialize, Serialize};
 use tracing::debug;
 
 use crate::{
-    compare::{Compare, Similarities},
-    query::Query,
+    compare::{Compare, ScoringProfile, Similarities},
+    explain::{self, Explanation},
+    query::{
+        ChainQuery, Filter, GenericArg, ItemKind, PrimitiveType, Qualifier, Query, QueryExpr,
+        QueryScope, ScopeKind, Type,
+    },
     Index,
 };
 use anyhow::Result;
@@ -24,19 +28,73 @@ pub struct Hit {
     pub link: String,
     pub docs: Option<String>,
     pub signature: String,
-    #[serde(skip_serializing, skip_deserializing)]
+    /// The trait a method is declared in or implements, e.g. `Iterator` for `Iterator::map`.
+    #[serde(rename = "trait")]
+    pub trait_: Option<String>,
+    /// The types named holes of the query were bound to, e.g. `u32` for the `?a` in
+    /// `fn (Vec<?a>, ?a) -> bool`, keyed by hole name without the `?`.
+    #[serde(default, skip_serializing_if = "BTreeMap::is_empty")]
+    pub bindings: BTreeMap<String, String>,
+    /// Why the hit matched, only serialized when kept with [`Hit::explained`].
+    #[serde(default, skip_serializing_if = "Similarities::is_empty")]
     similarities: Similarities,
+    /// How each part of the query compared, when searching with `explain`.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub explanation: Option<Explanation>,
 }
 
 impl Hit {
     pub fn similarities(&self) -> &Similarities {
         &self.similarities
     }
+
+    /// Keep the similarities and explanation of the hit when serializing it if `explain`, drop
+    /// them otherwise.
+    pub fn explained(mut self, explain: bool) -> Self {
+        if !explain {
+            self.similarities = Similarities::default();
+            self.explanation = None;
+        }
+        self
+    }
+}
+
+/// A chain of functions answering a multi-hop query, see [`Index::search_chain`].
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct ChainHit {
+    /// Functions in application order; each one takes the output of the previous one.
+    pub hits: Vec<Hit>,
+    /// Sum of the scores of every link, lower is better.
+    pub score: f32,
 }
 
-impl PartialOrd for Hit {
-    fn partial_cmp(&self, other: &Self) -> Option<std::cmp::Ordering> {
-        self.similarities.partial_cmp(&other.similarities)
+/// How a candidate matched a query.
+#[derive(Debug, Default)]
+struct Match {
+    sims: Similarities,
+    /// See [`Hit::bindings`].
+    bindings: BTreeMap<String, String>,
+    /// See [`Hit::explanation`], only recorded while explaining.
+    explanation: Option<Explanation>,
+}
+
+/// How many partial chains are kept between two hops of [`Index::search_chain`].
+const CHAIN_BEAM_WIDTH: usize = 64;
+
+/// An item, possibly a method of `impl_`, that a query can be compared against.
+struct Candidate<'a> {
+    item: &'a types::Item,
+    impl_: Option<&'a types::Impl>,
+    /// The name of the trait a method is declared in or implements, see [`Hit::trait_`].
+    trait_: Option<&'a str>,
+}
+
+impl Candidate<'_> {
+    fn function(&self) -> Option<&types::Function> {
+        match self.item.inner {
+            ItemEnum::Function(ref function) => Some(function),
+            _ => None,
+        }
     }
 }
 
@@ -112,12 +170,16 @@ impl Scope {
 impl Index {
     /// Perform search with given query and scope.
     ///
-    /// Returns [`Hit`]s whose similarity score outperforms given `threshold`.
+    /// Returns [`Hit`]s whose similarity score under `profile` outperforms given `threshold`,
+    /// see [`Self::evaluate`] for how combined queries are scored. Hits come with the
+    /// [`Explanation`] of how they compared if `explain`.
     pub fn search(
         &self,
-        query: &Query,
+        query: &QueryExpr,
         krates: &[CrateMetadata],
         threshold: f32,
+        profile: &ScoringProfile,
+        explain: bool,
     ) -> Result<Vec<Hit>> {
         tracing::debug!(
             "searching with query: {:?}, in crates: {:?}, threshold: {}",
@@ -126,7 +188,6 @@ impl Index {
             threshold
         );
         let mut hits = vec![];
-
         for krate_metadata in krates {
             let krate = self
                 .crates
@@ -138,85 +199,545 @@ impl Index {
                 .get(krate_metadata)
                 .expect("parent for a crate SHOULD ALWAYS be in 'parents' index");
 
-            for item in krate.index.values() {
-                tracing::trace!(?item);
-                match item.inner {
-                    types::ItemEnum::Function(ref f) => {
-                        let path = Self::path_and_link(krate, item, None, parents)?;
-                        tracing::trace!(?path);
-                        let sims = self.compare(query, item, krate, None);
-                        tracing::trace!(?sims);
-
-                        if sims.score() < threshold {
-                            debug!(?item, ?path, ?sims, score = ?sims.score());
-                            hits.push(Hit {
-                                id: item.id,
-                                name: item.name.clone().unwrap(), // SAFETY: all functions has its name.
-                                path: path.pathify(),
-                                link: path.link(),
-                                docs: item.docs.clone(),
-                                signature: format_fn_signature(
-                                    item.name.as_deref().unwrap_or(""),
-                                    &f.sig,
-                                ),
-                                similarities: sims,
-                            });
-                        }
+            for candidate in Self::candidates(krate, parents) {
+                tracing::trace!(item = ?candidate.item);
+                let evaluate =
+                    || self.evaluate(query, krate, &candidate, parents, threshold, profile);
+                if let Some(mut m) = evaluate() {
+                    // Only hits are compared again to be explained, as recording is costly.
+                    if explain {
+                        m = explain::explain(evaluate).0.unwrap_or(m);
                     }
-                    types::ItemEnum::Impl(ref impl_) if impl_.trait_.is_none() => {
-                        let assoc_items = impl_
-                            .items
-                            .iter()
-                            .map(|id| {
-                                krate.index.get(id).ok_or_else(|| {
-                                    search_error::item_not_found(id.0, krate_metadata)
-                                })
-                            })
-                            .collect::<Result<Vec<_>>>()?;
-                        for assoc_item in assoc_items {
-                            if let types::ItemEnum::Function(ref m) = assoc_item.inner {
-                                let path =
-                                    Self::path_and_link(krate, assoc_item, Some(impl_), parents)?;
-                                let sims = self.compare(query, assoc_item, krate, Some(impl_));
-
-                                if sims.score() < threshold {
-                                    hits.push(Hit {
-                                        id: assoc_item.id,
-                                        name: assoc_item.name.clone().unwrap(), // SAFETY: all methods has its name.
-                                        path: path.pathify(),
-                                        link: path.link(),
-                                        docs: assoc_item.docs.clone(),
-                                        signature: format_fn_signature(
-                                            assoc_item.name.as_deref().unwrap_or(""),
-                                            &m.sig,
-                                        ),
-                                        similarities: sims,
-                                    })
-                                }
-                            }
+                    debug!(item = ?candidate.item, sims = ?m.sims, score = ?m.sims.score_with(profile));
+                    // Methods of impls in private modules have no path to link to.
+                    match Self::hit(krate, &candidate, parents, m) {
+                        Ok(hit) => hits.push(hit),
+                        Err(err) => {
+                            tracing::trace!(item = ?candidate.item, %err, "skipping hit")
                         }
                     }
-                    // TODO(hkmatsumoto): Acknowledge trait method as well.
-                    _ => {}
                 }
             }
         }
 
-        hits.sort_unstable_by(|a, b| a.partial_cmp(b).unwrap());
+        hits.sort_unstable_by(|a, b| {
+            rank(a.similarities(), profile).total_cmp(&rank(b.similarities(), profile))
+        });
 
         debug!("found {} hits", hits.len());
         Ok(hits)
     }
 
-    #[tracing::instrument(skip(self, krate, query, item), fields(query = %query, item = %item))]
+    /// Perform a multi-hop search, returning chains of at most `depth` functions that turn
+    /// `query.from` into `query.to`.
+    ///
+    /// Every link of a chain feeds the output of the previous function into the first argument
+    /// of the next one, and must itself score below `threshold` under `profile`. Chains are
+    /// ranked by the sum of their link scores.
+    pub fn search_chain(
+        &self,
+        query: &ChainQuery,
+        krates: &[CrateMetadata],
+        threshold: f32,
+        depth: usize,
+        profile: &ScoringProfile,
+    ) -> Result<Vec<ChainHit>> {
+        tracing::debug!(
+            "searching chains with query: {}, in crates: {:?}, threshold: {}, depth: {}",
+            query,
+            krates,
+            threshold,
+            depth
+        );
+
+        let mut candidates = vec![];
+        for krate_metadata in krates {
+            let krate = self
+                .crates
+                .get(krate_metadata)
+                .ok_or_else(|| search_error::crate_not_found(krate_metadata))?;
+            let parents = self
+                .parents
+                .get(krate_metadata)
+                .expect("parent for a crate SHOULD ALWAYS be in 'parents' index");
+
+            for candidate in Self::candidates(krate, parents) {
+                let Some(function) = candidate.function() else {
+                    continue;
+                };
+                if function.sig.inputs.is_empty() || function.sig.output.is_none() {
+                    continue;
+                }
+                let mut generics = self.generics(candidate.item, krate, candidate.impl_);
+                generics
+                    .params
+                    .extend(function.generics.params.iter().cloned());
+                generics
+                    .where_predicates
+                    .extend(function.generics.where_predicates.iter().cloned());
+                candidates.push((krate, parents, candidate, generics));
+            }
+        }
+
+        struct Partial {
+            type_: Type,
+            links: Vec<(usize, Similarities)>,
+            score: f32,
+        }
+
+        let mut complete: Vec<(Vec<(usize, Similarities)>, f32)> = vec![];
+        let mut frontier = vec![Partial {
+            type_: query.from.clone(),
+            links: vec![],
+            score: 0.0,
+        }];
+
+        for _ in 0..depth {
+            let mut next = vec![];
+            for partial in &frontier {
+                for (idx, (krate, _, candidate, generics)) in candidates.iter().enumerate() {
+                    if partial.links.iter().any(|(i, _)| *i == idx) {
+                        continue;
+                    }
+                    let Some(function) = candidate.function() else {
+                        continue;
+                    };
+                    let sig = &function.sig;
+                    let (input, output) = match (sig.inputs.first(), sig.output.as_ref()) {
+                        (Some((_, input)), Some(output)) => (input, output),
+                        _ => continue,
+                    };
+
+                    let mut substs = HashMap::default();
+                    let sims = Similarities(partial.type_.compare(
+                        input,
+                        krate,
+                        profile,
+                        &mut generics.clone(),
+                        &mut substs,
+                    ));
+                    let link_score = rank(&sims, profile);
+                    if link_score >= threshold {
+                        continue;
+                    }
+                    let score = partial.score + link_score;
+
+                    let mut target_sims = query.to.compare(
+                        output,
+                        krate,
+                        profile,
+                        &mut generics.clone(),
+                        &mut substs.clone(),
+                    );
+                    let target_score = rank(&Similarities(target_sims.clone()), profile);
+                    if target_score < threshold {
+                        let mut links = partial.links.clone();
+                        let mut last = sims.clone();
+                        last.0.append(&mut target_sims);
+                        links.push((idx, last));
+                        complete.push((links, score + target_score));
+                    }
+
+                    if let Some(type_) = resolve_type(output, generics, &substs) {
+                        let mut links = partial.links.clone();
+                        links.push((idx, sims));
+                        next.push(Partial {
+                            type_,
+                            links,
+                            score,
+                        });
+                    }
+                }
+            }
+
+            next.sort_unstable_by(|a, b| a.score.total_cmp(&b.score));
+            next.truncate(CHAIN_BEAM_WIDTH);
+            frontier = next;
+        }
+
+        complete.sort_by(|(a, a_score), (b, b_score)| {
+            a_score.total_cmp(b_score).then(a.len().cmp(&b.len()))
+        });
+
+        let mut chains: Vec<ChainHit> = vec![];
+        for (links, score) in complete {
+            let hits = links
+                .into_iter()
+                .map(|(idx, sims)| {
+                    let (krate, parents, candidate, _) = &candidates[idx];
+                    Self::hit(
+                        krate,
+                        candidate,
+                        parents,
+                        Match {
+                            sims,
+                            ..Default::default()
+                        },
+                    )
+                })
+                .collect::<Result<Vec<_>>>();
+            // See `search` for hits without a path.
+            match hits {
+                Ok(hits) => chains.push(ChainHit { hits, score }),
+                Err(err) => tracing::trace!(%err, "skipping chain"),
+            }
+        }
+
+        debug!("found {} chains", chains.len());
+        Ok(chains)
+    }
+
+    /// Evaluate `expr` against `candidate`, returning the similarities it ranks by if it matches.
+    ///
+    /// Queries match when they score below `threshold`. A conjunction pools the similarities and
+    /// hole bindings of its operands and an alternation keeps those of its best scoring one,
+    /// while filters and negations only select candidates without being scored.
+    fn evaluate(
+        &self,
+        expr: &QueryExpr,
+        krate: &types::Crate,
+        candidate: &Candidate,
+        parents: &HashMap<types::Id, Parent>,
+        threshold: f32,
+        profile: &ScoringProfile,
+    ) -> Option<Match> {
+        let evaluate = |expr| self.evaluate(expr, krate, candidate, parents, threshold, profile);
+        match expr {
+            QueryExpr::Query(query) => {
+                if !query.targets(&candidate.item.inner) {
+                    return None;
+                }
+                if let Some(ref scope) = query.scope {
+                    if !Self::in_scope(krate, candidate, parents, scope) {
+                        return None;
+                    }
+                }
+                let m = self.matches(query, candidate.item, krate, candidate.impl_, profile);
+                tracing::trace!(sims = ?m.sims);
+                (rank(&m.sims, profile) < threshold).then_some(m)
+            }
+            QueryExpr::Filter(filter) => {
+                Self::filter(krate, candidate, parents, filter).then(Match::default)
+            }
+            QueryExpr::Not(expr) => evaluate(expr).is_none().then(Match::default),
+            QueryExpr::And(exprs) => {
+                let mut m = Match::default();
+                let mut explanations = vec![];
+                for expr in exprs {
+                    let mut operand = evaluate(expr)?;
+                    m.sims.0.append(&mut operand.sims.0);
+                    m.bindings.append(&mut operand.bindings);
+                    explanations.extend(operand.explanation);
+                }
+                m.explanation = match explanations.len() {
+                    0 | 1 => explanations.pop(),
+                    _ => Some(Explanation {
+                        label: expr.to_string(),
+                        score: (!m.sims.is_empty()).then(|| m.sims.score_with(profile)),
+                        similarities: vec![],
+                        children: explanations,
+                    }),
+                };
+                Some(m)
+            }
+            QueryExpr::Or(exprs) => exprs
+                .iter()
+                .filter_map(evaluate)
+                .min_by(|a, b| rank(&a.sims, profile).total_cmp(&rank(&b.sims, profile))),
+        }
+    }
+
+    /// Whether `candidate` satisfies `filter`.
+    fn filter(
+        krate: &types::Crate,
+        candidate: &Candidate,
+        parents: &HashMap<types::Id, Parent>,
+        filter: &Filter,
+    ) -> bool {
+        let item = candidate.item;
+        match filter {
+            Filter::Kind(kind) => {
+                let method = candidate.impl_.is_some() || candidate.trait_.is_some();
+                match (kind, &item.inner) {
+                    (ItemKind::Function, ItemEnum::Function(_)) => !method,
+                    (ItemKind::Method, ItemEnum::Function(_)) => method,
+                    (ItemKind::Macro, ItemEnum::Macro(_) | ItemEnum::ProcMacro(_))
+                    | (ItemKind::Struct, ItemEnum::Struct(_))
+                    | (ItemKind::Enum, ItemEnum::Enum(_))
+                    | (ItemKind::Union, ItemEnum::Union(_))
+                    | (ItemKind::Trait, ItemEnum::Trait(_))
+                    | (ItemKind::TypeAlias, ItemEnum::TypeAlias(_)) => true,
+                    _ => false,
+                }
+            }
+            // Crate names are normalized to use underscores by rustdoc.
+            Filter::Crate(name) => krate.name.as_deref() == Some(name.replace('-', "_").as_str()),
+            Filter::In(path) => {
+                let mut modules = vec![];
+                let mut cur = item.id;
+                while let Some(parent) = parents.get(&cur) {
+                    cur = match parent {
+                        Parent::Module(id) => {
+                            modules.extend(krate.index.get(id).and_then(|m| m.name.as_deref()));
+                            *id
+                        }
+                        Parent::Struct(id)
+                        | Parent::Enum(id)
+                        | Parent::Trait(id)
+                        | Parent::Impl(id) => *id,
+                    };
+                }
+                modules.reverse();
+                let segments = path.split("::").collect::<Vec<_>>();
+                modules.windows(segments.len()).any(|w| w == segments)
+            }
+            Filter::Is(qualifier) => match item.inner {
+                ItemEnum::Function(ref f) => match qualifier {
+                    Qualifier::Async => f.header.is_async,
+                    Qualifier::Unsafe => f.header.is_unsafe,
+                    Qualifier::Const => f.header.is_const,
+                },
+                _ => false,
+            },
+            Filter::Deprecated(deprecated) => item.deprecation.is_some() == *deprecated,
+        }
+    }
+
+    /// Enumerate every function, method, macro and type definition of `krate` that a query can
+    /// match.
+    ///
+    /// Methods are reached through their impl or trait, so that they carry the context `Self` is
+    /// resolved from. Synthetic and blanket impls are skipped as they only restate other items.
+    fn candidates<'a>(
+        krate: &'a types::Crate,
+        parents: &HashMap<types::Id, Parent>,
+    ) -> Vec<Candidate<'a>> {
+        let mut candidates = vec![];
+
+        for item in krate.index.values() {
+            match item.inner {
+                types::ItemEnum::Function(_)
+                    if matches!(
+                        parents.get(&item.id),
+                        Some(Parent::Impl(_) | Parent::Trait(_))
+                    ) => {}
+                types::ItemEnum::Function(_)
+                | types::ItemEnum::Macro(_)
+                | types::ItemEnum::ProcMacro(_)
+                | types::ItemEnum::Struct(_)
+                | types::ItemEnum::Enum(_)
+                | types::ItemEnum::Union(_)
+                | types::ItemEnum::TypeAlias(_) => candidates.push(Candidate {
+                    item,
+                    impl_: None,
+                    trait_: None,
+                }),
+                types::ItemEnum::Trait(ref trait_) => {
+                    candidates.push(Candidate {
+                        item,
+                        impl_: None,
+                        trait_: None,
+                    });
+                    for assoc_item in trait_.items.iter().filter_map(|id| krate.index.get(id)) {
+                        if let types::ItemEnum::Function(_) = assoc_item.inner {
+                            candidates.push(Candidate {
+                                item: assoc_item,
+                                impl_: None,
+                                trait_: item.name.as_deref(),
+                            });
+                        }
+                    }
+                }
+                types::ItemEnum::Impl(ref impl_)
+                    if !impl_.is_synthetic && impl_.blanket_impl.is_none() =>
+                {
+                    // Items of impls for foreign traits may live in other crates.
+                    for assoc_item in impl_.items.iter().filter_map(|id| krate.index.get(id)) {
+                        if let types::ItemEnum::Function(_) = assoc_item.inner {
+                            candidates.push(Candidate {
+                                item: assoc_item,
+                                impl_: Some(impl_),
+                                trait_: impl_.trait_.as_ref().map(|t| trait_name(krate, t)),
+                            });
+                        }
+                    }
+                }
+                _ => {}
+            }
+        }
+
+        candidates
+    }
+
+    /// Whether `candidate` is declared in, or implemented on, the owner or module named by `scope`.
+    fn in_scope(
+        krate: &types::Crate,
+        candidate: &Candidate,
+        parents: &HashMap<types::Id, Parent>,
+        scope: &QueryScope,
+    ) -> bool {
+        let named = |id: &types::Id| {
+            krate
+                .index
+                .get(id)
+                .is_some_and(|item| item.name.as_deref() == Some(scope.name.as_str()))
+        };
+        let impl_matches = |impl_: &types::Impl| match scope.kind {
+            ScopeKind::Struct | ScopeKind::Enum => match impl_.for_ {
+                types::Type::ResolvedPath(ref path) => {
+                    let kind = krate
+                        .index
+                        .get(&path.id)
+                        .map(|item| match item.inner {
+                            ItemEnum::Struct(_) => Some(types::ItemKind::Struct),
+                            ItemEnum::Enum(_) => Some(types::ItemKind::Enum),
+                            _ => None,
+                        })
+                        .unwrap_or_else(|| krate.paths.get(&path.id).map(|p| p.kind));
+                    let expected = match scope.kind {
+                        ScopeKind::Struct => types::ItemKind::Struct,
+                        _ => types::ItemKind::Enum,
+                    };
+                    path.path.split("::").last() == Some(scope.name.as_str())
+                        && kind == Some(expected)
+                }
+                _ => false,
+            },
+            ScopeKind::Trait => impl_
+                .trait_
+                .as_ref()
+                .is_some_and(|t| t.path.split("::").last() == Some(scope.name.as_str())),
+            ScopeKind::Module => false,
+        };
+
+        if candidate.impl_.is_some_and(impl_matches) {
+            return true;
+        }
+
+        let mut cur = candidate.item.id;
+        while let Some(parent) = parents.get(&cur) {
+            let matches = match (parent, scope.kind) {
+                (Parent::Module(id), ScopeKind::Module)
+                | (Parent::Struct(id), ScopeKind::Struct)
+                | (Parent::Enum(id), ScopeKind::Enum)
+                | (Parent::Trait(id), ScopeKind::Trait) => named(id),
+                (Parent::Impl(id), _) => krate.index.get(id).is_some_and(|item| match item.inner {
+                    ItemEnum::Impl(ref impl_) => impl_matches(impl_),
+                    _ => false,
+                }),
+                _ => false,
+            };
+            if matches {
+                return true;
+            }
+            cur = match parent {
+                Parent::Module(id)
+                | Parent::Struct(id)
+                | Parent::Enum(id)
+                | Parent::Trait(id)
+                | Parent::Impl(id) => *id,
+            };
+        }
+
+        false
+    }
+
+    fn hit(
+        krate: &types::Crate,
+        candidate: &Candidate,
+        parents: &HashMap<types::Id, Parent>,
+        m: Match,
+    ) -> Result<Hit> {
+        let item = candidate.item;
+        let path = Self::path_and_link(krate, item, candidate.impl_, parents)?;
+        tracing::trace!(?path);
+
+        let name = item.name.clone().unwrap(); // SAFETY: all candidates are named items.
+        let signature = match item.inner {
+            ItemEnum::Function(ref f) => format_fn_signature(&name, &f.sig),
+            ItemEnum::Macro(ref source) => source.split_whitespace().collect::<Vec<_>>().join(" "),
+            ItemEnum::ProcMacro(ref m) => match m.kind {
+                types::MacroKind::Bang => format!("{}!(..)", name),
+                types::MacroKind::Attr => format!("#[{}]", name),
+                types::MacroKind::Derive => format!("#[derive({})]", name),
+            },
+            ItemEnum::Struct(ref s) => format!("struct {}{}", name, s.generics),
+            ItemEnum::Enum(ref e) => format!("enum {}{}", name, e.generics),
+            ItemEnum::Union(ref u) => format!("union {}{}", name, u.generics),
+            ItemEnum::Trait(ref t) => format!("trait {}{}", name, t.generics),
+            ItemEnum::TypeAlias(ref t) => {
+                format!("type {}{} = {}", name, t.generics, render_type(&t.type_))
+            }
+            _ => String::new(),
+        };
+
+        Ok(Hit {
+            id: item.id,
+            name,
+            path: path.pathify(),
+            link: path.link(),
+            docs: item.docs.clone(),
+            signature,
+            trait_: candidate.trait_.map(str::to_owned),
+            bindings: m.bindings,
+            similarities: m.sims,
+            explanation: m.explanation,
+        })
+    }
+
+    /// Compare `query` with `item`, also explaining how each part of the query compared if
+    /// `explain`.
     pub fn compare(
         &self,
         query: &Query,
         item: &types::Item,
         krate: &types::Crate,
         impl_: Option<&types::Impl>,
-    ) -> Similarities {
+        profile: &ScoringProfile,
+        explain: bool,
+    ) -> (Similarities, Option<Explanation>) {
+        let matches = || self.matches(query, item, krate, impl_, profile);
+        let m = if explain {
+            explain::explain(matches).0
+        } else {
+            matches()
+        };
+        (m.sims, m.explanation)
+    }
+
+    /// Like [`Self::compare`], also returning what the named holes of `query` were bound to.
+    #[tracing::instrument(skip(self, krate, query, item, profile), fields(query = %query, item = %item))]
+    fn matches(
+        &self,
+        query: &Query,
+        item: &types::Item,
+        krate: &types::Crate,
+        impl_: Option<&types::Impl>,
+        profile: &ScoringProfile,
+    ) -> Match {
         tracing::trace!("Comparing {} with {}", query, item);
+        let mut generics = self.generics(item, krate, impl_);
+        let mut substs = HashMap::default();
+        let (sims, recorded) =
+            explain::detached(|| query.compare(item, krate, profile, &mut generics, &mut substs));
+        let bindings = substs
+            .into_iter()
+            .filter_map(|(name, ty)| Some((name.strip_prefix('?')?.to_owned(), ty.to_string())))
+            .collect();
+        Match {
+            sims: Similarities(sims),
+            bindings,
+            explanation: recorded.into_iter().next().map(|r| r.into_explanation()),
+        }
+    }
+
+    /// Generics in scope for `item`, with `Self` bound to its owner when it has one.
+    fn generics(
+        &self,
+        item: &types::Item,
+        krate: &types::Crate,
+        impl_: Option<&types::Impl>,
+    ) -> types::Generics {
         let mut generics = types::Generics::default();
         if let Some(impl_) = impl_ {
             generics = impl_.generics.clone();
@@ -256,6 +777,30 @@ impl Index {
                             rhs: types::Term::Type(for_),
                         });
                 }
+                Some(Parent::Trait(tid)) => {
+                    tracing::trace!("Found owner: {:?}", tid);
+                    let trait_ = krate.index.get(tid).unwrap();
+                    let ItemEnum::Trait(ref t) = trait_.inner else {
+                        unreachable!();
+                    };
+                    // Within a trait, `Self` is any implementor of it.
+                    generics = t.generics.clone();
+                    generics
+                        .where_predicates
+                        .push(types::WherePredicate::BoundPredicate {
+                            type_: types::Type::Generic("Self".to_owned()),
+                            bounds: vec![types::GenericBound::TraitBound {
+                                trait_: types::Path {
+                                    path: trait_.name.clone().unwrap_or_default(),
+                                    id: *tid,
+                                    args: None,
+                                },
+                                generic_params: vec![],
+                                modifier: types::TraitBoundModifier::None,
+                            }],
+                            generic_params: vec![],
+                        });
+                }
                 Some(p) => {
                     tracing::trace!("Found owner: {:?}", p);
                 }
@@ -264,21 +809,30 @@ impl Index {
                 }
             }
         }
-        let mut substs = HashMap::default();
-        let sims = query.compare(item, krate, &mut generics, &mut substs);
-        Similarities(sims)
+        generics
     }
 
     /// Given `item` and optional `impl_`, compute its path and rustdoc link to `item`.
     ///
-    /// `item` must be a function or a method, otherwise assertions will fail.
+    /// `item` must be a function, a method, a macro or a type definition, otherwise assertions
+    /// will fail.
     fn path_and_link(
         krate: &types::Crate,
         item: &types::Item,
         _impl_: Option<&types::Impl>,
         parents: &HashMap<types::Id, Parent>,
     ) -> Result<crate::Path> {
-        assert!(matches!(item.inner, types::ItemEnum::Function(_)));
+        assert!(matches!(
+            item.inner,
+            types::ItemEnum::Function(_)
+                | types::ItemEnum::Macro(_)
+                | types::ItemEnum::ProcMacro(_)
+                | types::ItemEnum::Struct(_)
+                | types::ItemEnum::Enum(_)
+                | types::ItemEnum::Union(_)
+                | types::ItemEnum::Trait(_)
+                | types::ItemEnum::TypeAlias(_)
+        ));
 
         let kinfo = krate.crate_metadata();
 
@@ -313,6 +867,150 @@ impl Index {
     }
 }
 
+/// The name of the trait `path` refers to, resolved through `krate` so that it doesn't depend on
+/// how the path was written, e.g. `Iterator` for `iter::Iterator`.
+fn trait_name<'a>(krate: &'a types::Crate, path: &'a types::Path) -> &'a str {
+    krate
+        .index
+        .get(&path.id)
+        .and_then(|item| item.name.as_deref())
+        .or_else(|| {
+            krate
+                .paths
+                .get(&path.id)
+                .and_then(|summary| summary.path.last())
+                .map(String::as_str)
+        })
+        .unwrap_or_else(|| path.path.rsplit("::").next().unwrap_or(&path.path))
+}
+
+/// The score hits are ordered by. Hits selected by filters alone are not scored and go last.
+fn rank(sims: &Similarities, profile: &ScoringProfile) -> f32 {
+    if sims.0.is_empty() {
+        f32::INFINITY
+    } else {
+        sims.score_with(profile)
+    }
+}
+
+/// Convert a candidate's type into a query type, so that it can be matched against the next
+/// function of a chain. Generics are replaced by what they were substituted with, if anything.
+pub(crate) fn resolve_type(
+    type_: &types::Type,
+    generics: &types::Generics,
+    substs: &HashMap<String, Type>,
+) -> Option<Type> {
+    let resolve = |t: &types::Type| resolve_type(t, generics, substs);
+
+    match type_ {
+        types::Type::Generic(name) if name == "Self" => {
+            generics.where_predicates.iter().find_map(|p| match p {
+                types::WherePredicate::EqPredicate {
+                    lhs: types::Type::Generic(lhs),
+                    rhs: types::Term::Type(rhs),
+                } if lhs == "Self" && !matches!(rhs, types::Type::Generic(_)) => resolve(rhs),
+                _ => None,
+            })
+        }
+        types::Type::Generic(name) => Some(
+            substs
+                .get(name)
+                .cloned()
+                .unwrap_or_else(|| Type::Generic(name.clone())),
+        ),
+        types::Type::Primitive(name) => PrimitiveType::from_name(name).map(Type::Primitive),
+        types::Type::ResolvedPath(path) => Some(resolve_path(path, generics, substs)),
+        types::Type::ImplTrait(bounds) => Some(Type::ImplTrait(
+            bounds
+                .iter()
+                .filter_map(|bound| match bound {
+                    types::GenericBound::TraitBound { trait_, .. } => {
+                        Some(crate::query::GenericBound::TraitBound(resolve_path(
+                            trait_, generics, substs,
+                        )))
+                    }
+                    _ => None,
+                })
+                .collect(),
+        )),
+        types::Type::DynTrait(dyn_) => Some(Type::DynTrait(
+            dyn_.traits
+                .iter()
+                .map(|poly| {
+                    crate::query::GenericBound::TraitBound(resolve_path(
+                        &poly.trait_,
+                        generics,
+                        substs,
+                    ))
+                })
+                .collect(),
+        )),
+        types::Type::QualifiedPath {
+            name,
+            self_type,
+            trait_,
+            ..
+        } => Some(Type::QualifiedPath {
+            name: name.clone(),
+            self_type: Box::new(resolve(self_type)?),
+            trait_: trait_
+                .as_ref()
+                .map(|trait_| Box::new(resolve_path(trait_, generics, substs))),
+        }),
+        types::Type::Tuple(types) => Some(Type::Tuple(types.iter().map(resolve).collect())),
+        types::Type::Slice(t) => Some(Type::Slice(resolve(t).map(Box::new))),
+        types::Type::Array { type_, len } => Some(Type::Array {
+            type_: resolve(type_).map(Box::new),
+            len: Some(len.clone()),
+        }),
+        types::Type::BorrowedRef {
+            is_mutable, type_, ..
+        } => Some(Type::BorrowedRef {
+            mutable: *is_mutable,
+            type_: Box::new(resolve(type_)?),
+        }),
+        types::Type::RawPointer { is_mutable, type_ } => Some(Type::RawPointer {
+            mutable: *is_mutable,
+            type_: Box::new(resolve(type_)?),
+        }),
+        _ => None,
+    }
+}
+
+/// Converts a resolved path of a candidate into the query type it answers.
+fn resolve_path(
+    path: &types::Path,
+    generics: &types::Generics,
+    substs: &HashMap<String, Type>,
+) -> Type {
+    let resolve = |t: &types::Type| resolve_type(t, generics, substs);
+
+    let args = match path.args.as_deref() {
+        Some(types::GenericArgs::AngleBracketed { args, .. }) => {
+            let args = args
+                .iter()
+                .filter_map(|arg| match arg {
+                    types::GenericArg::Type(t) => Some(resolve(t).map(GenericArg::Type)),
+                    types::GenericArg::Const(c) => Some(Some(GenericArg::Const(c.expr.clone()))),
+                    types::GenericArg::Lifetime(_) => None,
+                    _ => Some(None),
+                })
+                .collect::<Vec<_>>();
+            (!args.is_empty()).then(|| {
+                Box::new(crate::query::GenericArgs::AngleBracketed {
+                    args,
+                    constraints: vec![],
+                })
+            })
+        }
+        _ => None,
+    };
+    Type::UnresolvedPath {
+        name: path.path.split("::").last().unwrap_or_default().to_owned(),
+        args,
+    }
+}
+
 fn format_fn_signature(name: &str, decl: &types::FunctionSignature) -> String {
     let args = decl
         .inputs
@@ -342,7 +1040,7 @@ fn render_type(t: &types::Type) -> String {
             format!("({})", inner)
         }
         types::Type::Slice(inner) => format!("[{}]", render_type(inner)),
-        types::Type::Array { type_, .. } => format!("[{}]", render_type(type_)),
+        types::Type::Array { type_, len } => format!("[{}; {}]", render_type(type_), len),
         types::Type::RawPointer { is_mutable, type_ } => {
             let m = if *is_mutable { "mut" } else { "const" };
             format!("*{} {}", m, render_type(type_))
@@ -353,42 +1051,106 @@ fn render_type(t: &types::Type) -> String {
             let m = if *is_mutable { "mut " } else { "" };
             format!("&{}{}", m, render_type(type_))
         }
-        types::Type::ResolvedPath(path) => {
-            let mut s = path.path.clone();
-            if let Some(ga) = &path.args {
-                if let types::GenericArgs::AngleBracketed { args, .. } =
-                    (ga as &Box<GenericArgs>).as_ref()
-                {
-                    let inner = args
-                        .iter()
-                        .filter_map(|ga| match ga {
-                            types::GenericArg::Type(t) => Some(render_type(t)),
-                            _ => None,
-                        })
-                        .collect::<Vec<_>>()
-                        .join(", ");
-                    if !inner.is_empty() {
-                        s.push('<');
-                        s.push_str(&inner);
-                        s.push('>');
-                    }
-                }
+        types::Type::ResolvedPath(path) => render_path(path),
+        types::Type::ImplTrait(bounds) => {
+            let bounds = bounds
+                .iter()
+                .filter_map(|b| match b {
+                    types::GenericBound::TraitBound { trait_, .. } => Some(render_path(trait_)),
+                    _ => None,
+                })
+                .collect::<Vec<_>>()
+                .join(" + ");
+            format!("impl {}", bounds)
+        }
+        types::Type::FunctionPointer(fp) => {
+            let args = fp
+                .sig
+                .inputs
+                .iter()
+                .map(|(_, t)| render_type(t))
+                .collect::<Vec<_>>()
+                .join(", ");
+            match &fp.sig.output {
+                Some(output) => format!("fn({}) -> {}", args, render_type(output)),
+                None => format!("fn({})", args),
             }
-            s
         }
-        types::Type::QualifiedPath { name, .. } => name.clone(),
+        types::Type::DynTrait(dyn_) => {
+            let traits = dyn_
+                .traits
+                .iter()
+                .map(|poly| render_path(&poly.trait_))
+                .collect::<Vec<_>>()
+                .join(" + ");
+            format!("dyn {}", traits)
+        }
+        types::Type::QualifiedPath {
+            name,
+            self_type,
+            trait_: Some(trait_),
+            ..
+        } => format!(
+            "<{} as {}>::{}",
+            render_type(self_type),
+            render_path(trait_),
+            name
+        ),
+        types::Type::QualifiedPath {
+            name, self_type, ..
+        } => format!("{}::{}", render_type(self_type), name),
         _ => "_".to_string(),
     }
 }
 
+fn render_path(path: &types::Path) -> String {
+    let mut s = path.path.clone();
+    if let Some(types::GenericArgs::Parenthesized { inputs, output }) = path.args.as_deref() {
+        let inputs = inputs
+            .iter()
+            .map(render_type)
+            .collect::<Vec<_>>()
+            .join(", ");
+        s.push_str(&format!("({})", inputs));
+        if let Some(output) = output {
+            s.push_str(&format!(" -> {}", render_type(output)));
+        }
+    }
+    if let Some(types::GenericArgs::AngleBracketed { args, constraints }) = path.args.as_deref() {
+        let inner = args
+            .iter()
+            .filter_map(|ga| match ga {
+                types::GenericArg::Type(t) => Some(render_type(t)),
+                types::GenericArg::Const(c) => Some(c.expr.clone()),
+                _ => None,
+            })
+            .chain(constraints.iter().filter_map(|c| match &c.binding {
+                types::AssocItemConstraintKind::Equality(types::Term::Type(t)) => {
+                    Some(format!("{} = {}", c.name, render_type(t)))
+                }
+                _ => None,
+            }))
+            .collect::<Vec<_>>()
+            .join(", ");
+        if !inner.is_empty() {
+            s.push('<');
+            s.push_str(&inner);
+            s.push('>');
+        }
+    }
+    s
+}
+
 #[cfg(test)]
 mod tests {
     use std::collections::HashSet;
 
     use super::*;
     use crate::compare::{
+        Category,
         DiscreteSimilarity::{self},
         Similarity::*,
+        SimilarityReason,
     };
     use crate::query::{FnDecl, FnRetTy, Function};
     use crate::types::{FunctionHeader, Target};
@@ -442,6 +1204,7 @@ mod tests {
     #[test]
     fn compare_symbol() {
         let query = Query {
+            scope: None,
             name: Some("foo".to_owned()),
             kind: None,
         };
@@ -453,10 +1216,17 @@ mod tests {
         let mut substs = HashMap::default();
 
         assert_eq!(
-            query.compare(&item, &krate, &mut generics, &mut substs),
+            query.compare(
+                &item,
+                &krate,
+                &ScoringProfile::default(),
+                &mut generics,
+                &mut substs
+            ),
             vec![Continuous {
                 value: 0.0,
-                reason: "symbol name distance".to_string()
+                reason: SimilarityReason::SymbolNameDistance,
+                category: Category::Name,
             }]
         )
     }
@@ -467,8 +1237,11 @@ mod tests {
             decl: FnDecl {
                 inputs: Some(vec![]),
                 output: Some(FnRetTy::DefaultReturn),
+                unordered: false,
+                receiver: None,
             },
             qualifiers: HashSet::new(),
+            generics: crate::query::Generics::default(),
         };
 
         let i = foo();
@@ -478,17 +1251,619 @@ mod tests {
         let mut substs = HashMap::default();
 
         assert_eq!(
-            q.compare(&i, &krate, &mut generics, &mut substs),
+            q.compare(
+                &i,
+                &krate,
+                &ScoringProfile::default(),
+                &mut generics,
+                &mut substs
+            ),
             vec![
                 Discrete {
                     kind: DiscreteSimilarity::Equivalent,
-                    reason: "no arguments".to_string()
+                    reason: SimilarityReason::NoArguments,
+                    category: Category::Arity,
                 },
                 Discrete {
                     kind: DiscreteSimilarity::Equivalent,
-                    reason: "unit return".to_string()
+                    reason: SimilarityReason::UnitReturn,
+                    category: Category::Return,
                 }
             ]
         )
     }
+
+    /// Score of `candidate` for the function query `query`, under the default profile.
+    fn score(query: &str, candidate: &types::Function, krate: &types::Crate) -> f32 {
+        let (_, query) = crate::query::parse::parse_query(query).unwrap();
+        let Some(crate::query::QueryKind::FunctionQuery(q)) = query.kind else {
+            panic!("expected a function query");
+        };
+        let profile = ScoringProfile::default();
+        let mut generics = types::Generics::default();
+        let mut substs = HashMap::default();
+        Similarities(q.compare(candidate, krate, &profile, &mut generics, &mut substs))
+            .score_with(&profile)
+    }
+
+    /// Returns a function which will be expressed as `fn id<T: bounds..>(x: T) -> T`.
+    fn id_with_bounds(bounds: &[&str]) -> types::Function {
+        let bounds = bounds
+            .iter()
+            .map(|name| types::GenericBound::TraitBound {
+                trait_: types::Path {
+                    path: name.to_string(),
+                    id: types::Id(1),
+                    args: None,
+                },
+                generic_params: vec![],
+                modifier: types::TraitBoundModifier::None,
+            })
+            .collect();
+        let t = types::Type::Generic("T".to_owned());
+
+        types::Function {
+            generics: types::Generics {
+                params: vec![types::GenericParamDef {
+                    name: "T".to_owned(),
+                    kind: types::GenericParamDefKind::Type {
+                        bounds,
+                        default: None,
+                        is_synthetic: false,
+                    },
+                }],
+                where_predicates: vec![],
+            },
+            sig: types::FunctionSignature {
+                inputs: vec![("x".to_owned(), t.clone())],
+                output: Some(t),
+                is_c_variadic: false,
+            },
+            ..foo()
+        }
+    }
+
+    #[test]
+    fn compare_bounded_generics() {
+        let krate = krate();
+        let score = |i: &types::Function| score("fn <K: Hash + Eq>(K) -> K", i, &krate);
+
+        let bounded = score(&id_with_bounds(&["Eq", "std::hash::Hash"]));
+        let partially_bounded = score(&id_with_bounds(&["Hash"]));
+        let unbounded = score(&id_with_bounds(&[]));
+
+        assert!(bounded < partially_bounded);
+        assert!(partially_bounded < unbounded);
+    }
+
+    #[test]
+    fn compare_loosely_typed_signatures() {
+        let krate = krate();
+        let score = |i: &types::Function| score("fn (u8) -> u8", i, &krate);
+
+        // Both substitute `T` by `u8`, but only one of them would for any type.
+        let bounded = score(&id_with_bounds(&["Copy"]));
+        let unbounded = score(&id_with_bounds(&[]));
+        assert!(bounded < unbounded);
+        assert!(unbounded < 0.4);
+    }
+
+    #[test]
+    fn compare_std_trait_bounds() {
+        // `fn id<T: Write>(x: T) -> T`, for `io::Write` or `fmt::Write`.
+        let unmet = |trait_: &[&str], query: &str| {
+            let mut krate = krate();
+            krate.paths.insert(
+                types::Id(1),
+                types::ItemSummary {
+                    crate_id: 1,
+                    path: trait_.iter().map(|s| s.to_string()).collect(),
+                    kind: types::ItemKind::Trait,
+                },
+            );
+            let (_, query) = crate::query::parse::parse_query(query).unwrap();
+            let Some(crate::query::QueryKind::FunctionQuery(q)) = query.kind else {
+                panic!("expected a function query");
+            };
+            let mut generics = types::Generics::default();
+            let mut substs = HashMap::default();
+            q.compare(
+                &id_with_bounds(&["Write"]),
+                &krate,
+                &ScoringProfile::default(),
+                &mut generics,
+                &mut substs,
+            )
+            .iter()
+            .any(|sim| matches!(sim.reason(), SimilarityReason::UnmetBound { .. }))
+        };
+
+        let io = ["std", "io", "Write"];
+        let fmt = ["core", "fmt", "Write"];
+        assert!(unmet(&io, "fn (u8) -> u8"));
+        assert!(!unmet(&io, "fn (File) -> File"));
+        // Types missing from the table are not known not to implement the trait.
+        assert!(!unmet(&io, "fn (String) -> String"));
+        assert!(!unmet(&fmt, "fn (String) -> String"));
+        assert!(unmet(&fmt, "fn (char) -> char"));
+    }
+
+    #[test]
+    fn compare_unordered_arguments() {
+        let prims = [
+            "u8", "u16", "u32", "u64", "i8", "i16", "i32", "i64", "bool", "char",
+        ];
+        let function = types::Function {
+            sig: types::FunctionSignature {
+                inputs: prims
+                    .iter()
+                    .map(|p| (String::new(), types::Type::Primitive(p.to_string())))
+                    .collect(),
+                output: None,
+                is_c_variadic: false,
+            },
+            ..foo()
+        };
+        let reversed = prims.iter().rev().copied().collect::<Vec<_>>().join(", ");
+        let (_, query) = crate::query::parse::parse_query(&format!("fn {{{}}}", reversed)).unwrap();
+        let Some(crate::query::QueryKind::FunctionQuery(q)) = query.kind else {
+            panic!("expected a function query");
+        };
+
+        let mut generics = types::Generics::default();
+        let mut substs = HashMap::default();
+        let sims = Similarities(q.compare(
+            &function,
+            &krate(),
+            &ScoringProfile::default(),
+            &mut generics,
+            &mut substs,
+        ));
+        assert!(sims.0.iter().any(|sim| *sim.reason()
+            == SimilarityReason::ArgumentsReordered((0..prims.len()).rev().map(Some).collect())));
+        // Every argument matches its type, so reordering them is all that is held against it.
+        assert!(sims
+            .0
+            .iter()
+            .filter(|sim| !matches!(sim.reason(), SimilarityReason::ArgumentsReordered(_)))
+            .all(|sim| sim.score() == 0.0));
+    }
+
+    /// `fn collect<I>(iter: I) -> Vec<T> where I: IntoIterator<Item = T>`, optionally without its
+    /// where clause.
+    fn collect(with_where_clause: bool) -> types::Function {
+        let path = |path: &str, args: Option<types::GenericArgs>| types::Path {
+            path: path.to_owned(),
+            id: types::Id(1),
+            args: args.map(Box::new),
+        };
+        let t = types::Type::Generic("T".to_owned());
+        let i = types::Type::Generic("I".to_owned());
+        let param = |name: &str| types::GenericParamDef {
+            name: name.to_owned(),
+            kind: types::GenericParamDefKind::Type {
+                bounds: vec![],
+                default: None,
+                is_synthetic: false,
+            },
+        };
+        let into_iterator = types::GenericBound::TraitBound {
+            trait_: path(
+                "IntoIterator",
+                Some(types::GenericArgs::AngleBracketed {
+                    args: vec![],
+                    constraints: vec![types::AssocItemConstraint {
+                        name: "Item".to_owned(),
+                        args: None,
+                        binding: types::AssocItemConstraintKind::Equality(types::Term::Type(
+                            t.clone(),
+                        )),
+                    }],
+                }),
+            ),
+            generic_params: vec![],
+            modifier: types::TraitBoundModifier::None,
+        };
+
+        types::Function {
+            generics: types::Generics {
+                params: vec![param("I"), param("T")],
+                where_predicates: if with_where_clause {
+                    vec![types::WherePredicate::BoundPredicate {
+                        type_: i.clone(),
+                        bounds: vec![into_iterator],
+                        generic_params: vec![],
+                    }]
+                } else {
+                    vec![]
+                },
+            },
+            sig: types::FunctionSignature {
+                inputs: vec![("iter".to_owned(), i)],
+                output: Some(types::Type::ResolvedPath(path(
+                    "Vec",
+                    Some(types::GenericArgs::AngleBracketed {
+                        args: vec![types::GenericArg::Type(t)],
+                        constraints: vec![],
+                    }),
+                ))),
+                is_c_variadic: false,
+            },
+            ..foo()
+        }
+    }
+
+    #[test]
+    fn compare_where_predicates() {
+        let (_, query) =
+            crate::query::parse::parse_query("fn (I) -> Vec<T> where I: IntoIterator<Item = T>")
+                .unwrap();
+        let Some(crate::query::QueryKind::FunctionQuery(q)) = query.kind else {
+            panic!("expected a function query");
+        };
+
+        let krate = krate();
+        let mut generics = types::Generics::default();
+        let mut substs = HashMap::default();
+        let sims = q.compare(
+            &collect(true),
+            &krate,
+            &ScoringProfile::default(),
+            &mut generics,
+            &mut substs,
+        );
+        assert!(sims.iter().all(|sim| sim.score() < 1.0), "{sims:?}");
+
+        let mut generics = types::Generics::default();
+        let mut substs = HashMap::default();
+        let sims = q.compare(
+            &collect(false),
+            &krate,
+            &ScoringProfile::default(),
+            &mut generics,
+            &mut substs,
+        );
+        assert!(sims.contains(&Discrete {
+            kind: DiscreteSimilarity::Different,
+            reason: SimilarityReason::MissingTraitBound,
+            category: Category::Generic,
+        }));
+    }
+
+    /// Returns a function which will be expressed as `fn foo() -> <output>`, where the output
+    /// is either `impl Iterator<Item = u8>` or `Box<dyn Iterator<Item = u8>>`.
+    fn iter_bytes(boxed: bool) -> types::Function {
+        let iterator = types::Path {
+            path: "Iterator".to_owned(),
+            id: types::Id(1),
+            args: Some(Box::new(types::GenericArgs::AngleBracketed {
+                args: vec![],
+                constraints: vec![types::AssocItemConstraint {
+                    name: "Item".to_owned(),
+                    args: None,
+                    binding: types::AssocItemConstraintKind::Equality(types::Term::Type(
+                        types::Type::Primitive("u8".to_owned()),
+                    )),
+                }],
+            })),
+        };
+        let output = if boxed {
+            types::Type::ResolvedPath(types::Path {
+                path: "Box".to_owned(),
+                id: types::Id(2),
+                args: Some(Box::new(types::GenericArgs::AngleBracketed {
+                    args: vec![types::GenericArg::Type(types::Type::DynTrait(
+                        types::DynTrait {
+                            traits: vec![types::PolyTrait {
+                                trait_: iterator,
+                                generic_params: vec![],
+                            }],
+                            lifetime: None,
+                        },
+                    ))],
+                    constraints: vec![],
+                })),
+            })
+        } else {
+            types::Type::ImplTrait(vec![types::GenericBound::TraitBound {
+                trait_: iterator,
+                generic_params: vec![],
+                modifier: types::TraitBoundModifier::None,
+            }])
+        };
+
+        types::Function {
+            sig: types::FunctionSignature {
+                inputs: vec![],
+                output: Some(output),
+                is_c_variadic: false,
+            },
+            ..foo()
+        }
+    }
+
+    #[test]
+    fn compare_impl_and_dyn_trait() {
+        let krate = krate();
+        let score = |query: &str, i: &types::Function| score(query, i, &krate);
+
+        let opaque = "fn () -> impl Iterator<Item = u8>";
+        assert_eq!(score(opaque, &iter_bytes(false)), 0.0);
+        assert!(score(opaque, &iter_bytes(false)) < score(opaque, &iter_bytes(true)));
+        assert!(
+            score(opaque, &iter_bytes(false))
+                < score("fn () -> impl Iterator<Item = u16>", &iter_bytes(false))
+        );
+
+        let boxed = "fn () -> Box<dyn Iterator<Item = u8>>";
+        assert_eq!(score(boxed, &iter_bytes(true)), 0.0);
+        assert!(score(boxed, &iter_bytes(true)) < score(boxed, &iter_bytes(false)));
+    }
+
+    /// Returns a function which will be expressed as
+    /// `fn filter<T, F: FnMut(&T) -> bool>(v: Vec<T>, f: F) -> Vec<T>`.
+    fn filter() -> types::Function {
+        let t = types::Type::Generic("T".to_owned());
+        let vec_t = types::Type::ResolvedPath(types::Path {
+            path: "Vec".to_owned(),
+            id: types::Id(1),
+            args: Some(Box::new(types::GenericArgs::AngleBracketed {
+                args: vec![types::GenericArg::Type(t.clone())],
+                constraints: vec![],
+            })),
+        });
+        let fn_mut = types::GenericBound::TraitBound {
+            trait_: types::Path {
+                path: "FnMut".to_owned(),
+                id: types::Id(2),
+                args: Some(Box::new(types::GenericArgs::Parenthesized {
+                    inputs: vec![types::Type::BorrowedRef {
+                        lifetime: None,
+                        is_mutable: false,
+                        type_: Box::new(t),
+                    }],
+                    output: Some(types::Type::Primitive("bool".to_owned())),
+                })),
+            },
+            generic_params: vec![],
+            modifier: types::TraitBoundModifier::None,
+        };
+        let param = |name: &str, bounds| types::GenericParamDef {
+            name: name.to_owned(),
+            kind: types::GenericParamDefKind::Type {
+                bounds,
+                default: None,
+                is_synthetic: false,
+            },
+        };
+
+        types::Function {
+            generics: types::Generics {
+                params: vec![param("T", vec![]), param("F", vec![fn_mut])],
+                where_predicates: vec![],
+            },
+            sig: types::FunctionSignature {
+                inputs: vec![
+                    ("v".to_owned(), vec_t.clone()),
+                    ("f".to_owned(), types::Type::Generic("F".to_owned())),
+                ],
+                output: Some(vec_t),
+                is_c_variadic: false,
+            },
+            ..foo()
+        }
+    }
+
+    #[test]
+    fn compare_closures() {
+        let krate = krate();
+        let score = |query: &str| score(query, &filter(), &krate);
+
+        let closure = score("fn (Vec<T>, FnMut(&T) -> bool) -> Vec<T>");
+        let bounded = score("fn (Vec<T>, F) -> Vec<T> where F: FnMut(&T) -> bool");
+        let looser = score("fn (Vec<T>, Fn(&T) -> bool) -> Vec<T>");
+        let pointer = score("fn (Vec<T>, fn(&T) -> bool) -> Vec<T>");
+        let mistyped = score("fn (Vec<T>, FnMut(&T) -> u8) -> Vec<T>");
+
+        assert!(closure < looser, "{closure} < {looser}");
+        assert!(closure < pointer, "{closure} < {pointer}");
+        assert!(looser < mistyped, "{looser} < {mistyped}");
+        assert!(bounded < mistyped, "{bounded} < {mistyped}");
+    }
+
+    #[test]
+    fn compare_projections() {
+        // struct Bytes; impl Iterator for Bytes { type Item = u8; .. }
+        let path = |path: &str, id: u32| types::Path {
+            path: path.to_owned(),
+            id: types::Id(id),
+            args: None,
+        };
+        let bytes = types::Type::ResolvedPath(path("Bytes", 1));
+        let mut krate = krate();
+        krate.index.insert(
+            types::Id(1),
+            item(
+                "Bytes".to_owned(),
+                types::ItemEnum::Struct(types::Struct {
+                    kind: types::StructKind::Unit,
+                    generics: foo().generics,
+                    impls: vec![types::Id(2)],
+                }),
+            ),
+        );
+        krate.index.insert(
+            types::Id(2),
+            types::Item {
+                name: None,
+                ..item(
+                    String::new(),
+                    types::ItemEnum::Impl(types::Impl {
+                        is_unsafe: false,
+                        generics: foo().generics,
+                        provided_trait_methods: vec![],
+                        trait_: Some(path("Iterator", 3)),
+                        for_: bytes.clone(),
+                        items: vec![types::Id(4)],
+                        is_negative: false,
+                        is_synthetic: false,
+                        blanket_impl: None,
+                    }),
+                )
+            },
+        );
+        krate.index.insert(
+            types::Id(4),
+            item(
+                "Item".to_owned(),
+                types::ItemEnum::AssocType {
+                    generics: foo().generics,
+                    bounds: vec![],
+                    type_: Some(types::Type::Primitive("u8".to_owned())),
+                },
+            ),
+        );
+
+        // fn next_byte(b: Bytes) -> <Bytes as Iterator>::Item
+        let next_byte = types::Function {
+            sig: types::FunctionSignature {
+                inputs: vec![("b".to_owned(), bytes.clone())],
+                output: Some(types::Type::QualifiedPath {
+                    name: "Item".to_owned(),
+                    args: None,
+                    self_type: Box::new(bytes),
+                    trait_: Some(path("Iterator", 3)),
+                }),
+                is_c_variadic: false,
+            },
+            ..foo()
+        };
+
+        let score = |query: &str| score(query, &next_byte, &krate);
+
+        assert_eq!(score("fn (Bytes) -> <Bytes as Iterator>::Item"), 0.0);
+        assert_eq!(score("fn (Bytes) -> u8"), 0.0);
+        assert!(score("fn (Bytes) -> u8") < score("fn (Bytes) -> u16"));
+        assert!(
+            score("fn (Bytes) -> <Bytes as Iterator>::Item")
+                < score("fn (Bytes) -> <Bytes as IntoIterator>::Item")
+        );
+    }
+
+    #[test]
+    fn compare_macro_fragments() {
+        let make_vec = item(
+            "make_vec".to_owned(),
+            types::ItemEnum::Macro(
+                "macro_rules! make_vec {\n    ($($elem:expr),* $(,)?) => { ... };\n}".to_owned(),
+            ),
+        );
+        let define_newtype = item(
+            "define_newtype".to_owned(),
+            types::ItemEnum::Macro(
+                "macro_rules! define_newtype {\n    ($name:ident, $inner:ty) => { ... };\n}"
+                    .to_owned(),
+            ),
+        );
+        let (_, query) = crate::query::parse::parse_query("macro (ident, ty)").unwrap();
+
+        let krate = krate();
+        let score = |item: &types::Item| {
+            let mut generics = types::Generics::default();
+            let mut substs = HashMap::default();
+            Similarities(query.compare(
+                item,
+                &krate,
+                &ScoringProfile::default(),
+                &mut generics,
+                &mut substs,
+            ))
+            .score_with(&ScoringProfile::default())
+        };
+
+        assert_eq!(score(&define_newtype), 0.0);
+        assert!(score(&make_vec) > score(&define_newtype));
+    }
+
+    #[test]
+    fn validate_scoring_profiles() {
+        assert!(ScoringProfile::default().validate().is_ok());
+        let negative = ScoringProfile {
+            name: -1.0,
+            ..Default::default()
+        };
+        assert!(negative.validate().is_err());
+        let subequal = ScoringProfile {
+            subequal: 1.5,
+            ..Default::default()
+        };
+        assert!(subequal.validate().is_err());
+        let zero = toml::from_str::<ScoringProfile>(
+            "subequal = 0.25\nname = 0.0\nargument = 0.0\nreturn = 0.0\nqualifier = 0.0\n\
+             generic = 0.0\nwrapper = 0.0\narity = 0.0\nspecificity = 0.0\nother = 0.0\n",
+        )
+        .unwrap();
+        assert!(zero.validate().is_err());
+
+        // Similarities weighing nothing still have a score.
+        let sims = Similarities(vec![Discrete {
+            kind: DiscreteSimilarity::Different,
+            reason: SimilarityReason::LooselyTypedSignature,
+            category: Category::Specificity,
+        }]);
+        assert_eq!(sims.score_with(&zero), 0.0);
+    }
+
+    #[test]
+    fn load_scoring_profiles() {
+        let dir = tempfile::tempdir().unwrap();
+        std::fs::write(dir.path().join("names.toml"), "name = 4.0\n").unwrap();
+        std::fs::write(dir.path().join("names.json"), r#"{"name": 4.0}"#).unwrap();
+        let profile = ScoringProfile::load(dir.path().join("names.toml")).unwrap();
+        assert_eq!(
+            profile,
+            ScoringProfile::load(dir.path().join("names.json")).unwrap()
+        );
+        assert_eq!(profile.name, 4.0);
+        assert_eq!(profile.subequal, ScoringProfile::default().subequal);
+
+        std::fs::write(
+            dir.path().join("edits.toml"),
+            "[names]\nstrategy = \"levenshtein\"\n",
+        )
+        .unwrap();
+        let edits = ScoringProfile::load(dir.path().join("edits.toml")).unwrap();
+        assert_eq!(edits.names, crate::names::NameMatching::Levenshtein);
+
+        std::fs::write(dir.path().join("negative.toml"), "name = -1.0\n").unwrap();
+        assert!(ScoringProfile::load(dir.path().join("negative.toml")).is_err());
+    }
+
+    #[test]
+    fn serialize_explained_hits() {
+        let hit = Hit {
+            id: types::Id(0),
+            name: "word_count".to_owned(),
+            path: vec!["test".to_owned(), "word_count".to_owned()],
+            link: "test/fn.word_count.html".to_owned(),
+            docs: None,
+            signature: "fn word_count(&str) -> usize".to_owned(),
+            trait_: None,
+            bindings: BTreeMap::new(),
+            similarities: Similarities(vec![Discrete {
+                kind: DiscreteSimilarity::Different,
+                reason: SimilarityReason::MissingArgument { index: 1 },
+                category: Category::Arity,
+            }]),
+            explanation: None,
+        };
+
+        let json = serde_json::to_value(hit.clone().explained(true)).unwrap();
+        let roundtrip: Hit = serde_json::from_value(json).unwrap();
+        assert_eq!(roundtrip, hit);
+
+        let json = serde_json::to_value(hit.explained(false)).unwrap();
+        assert!(json.get("similarities").is_none());
+        assert!(json.get("explanation").is_none());
+    }
 }
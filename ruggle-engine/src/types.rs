@@ -69,16 +69,16 @@ impl CrateDependencyTree {
                 | ItemEnum::Union(_)
                 | ItemEnum::TypeAlias(_)
                 | ItemEnum::Primitive(_) => {
-                    let path = reconstruct_path_for_local(krate, id, &parents).expect(&format!(
-                        "Item({})[{}] does not have a valid path",
-                        id.0, item
-                    ));
+                    let path =
+                        reconstruct_path_for_local(krate, id, &parents).unwrap_or_else(|| {
+                            panic!("Item({})[{}] does not have a valid path", id.0, item)
+                        });
                     let path_iter = path
                         .modules
                         .iter()
-                        .map(|m| m.id.clone())
-                        .chain(path.owner.iter().map(|p| p.id.clone()))
-                        .chain(once(path.item.id.clone()));
+                        .map(|m| m.id)
+                        .chain(path.owner.iter().map(|p| p.id))
+                        .chain(once(path.item.id));
 
                     PathTree::insert_path(&mut children, path_iter);
                 }
@@ -1970,7 +1970,7 @@ impl Display for Path {
             self.path,
             self.args
                 .as_ref()
-                .map(|args| format!("<{}>", args.to_string()))
+                .map(|args| format!("<{}>", args))
                 .unwrap_or_default()
         )
     }
@@ -2,8 +2,11 @@ use std::path::{Path, PathBuf};
 
 use anyhow::Context as _;
 use anyhow::Result;
-use ruggle_engine::search::Hit;
-use ruggle_server::{generate_bin_index, make_index, make_sets, perform_search, shake_index};
+use ruggle_engine::{compare::ScoringProfile, query::parse::QueryParseError, search::Hit};
+use ruggle_server::{
+    generate_bin_index, make_index, make_sets, perform_search_with_profile, shake_index,
+    SearchError,
+};
 
 use structopt::StructOpt;
 use tracing::info;
@@ -31,10 +34,19 @@ struct Cli {
     #[structopt(long, default_value = "0.4")]
     threshold: f32,
 
+    /// Scoring profile file (TOML, or JSON with a `.json` extension)
+    /// The server is asked for the profile named after the file stem.
+    #[structopt(long, parse(from_os_str))]
+    profile: Option<PathBuf>,
+
     /// Output as JSON
     #[structopt(long)]
     json: bool,
 
+    /// Show why each hit matched, as a tree of the parts of the query
+    #[structopt(long)]
+    explain: bool,
+
     /// Query string
     #[structopt(long)]
     query: String,
@@ -61,10 +73,12 @@ async fn ask_server(
     query: &str,
     limit: usize,
     threshold: f32,
+    profile: Option<&str>,
+    explain: bool,
 ) -> Result<Vec<Hit>> {
     let client = reqwest::Client::new();
     tracing::debug!("(scope={}, query={})", scope, query);
-    let url = format!(
+    let mut url = format!(
         "{}/search?scope={}&query={}&limit={}&threshold={}",
         host,
         urlencoding::encode(scope),
@@ -72,6 +86,12 @@ async fn ask_server(
         limit,
         threshold
     );
+    if let Some(profile) = profile {
+        url.push_str(&format!("&profile={}", urlencoding::encode(profile)));
+    }
+    if explain {
+        url.push_str("&explain=true");
+    }
     tracing::debug!("requesting {}", url);
 
     let res = client.get(&url).send().await.context("request failed")?;
@@ -79,6 +99,13 @@ async fn ask_server(
     let status = res.status();
     if !status.is_success() {
         let text = res.text().await.unwrap_or_default();
+        if let Ok(SearchError {
+            error,
+            parse: Some(parse),
+        }) = serde_json::from_str(&text)
+        {
+            return Err(anyhow::Error::new(parse).context(error));
+        }
         anyhow::bail!("{}: {}", status, text);
     }
 
@@ -119,8 +146,26 @@ async fn main() -> Result<()> {
         return Ok(());
     }
 
+    let profile = match &cli.profile {
+        Some(path) => ScoringProfile::load(path)?,
+        None => ScoringProfile::default(),
+    };
     let hits = if cli.server {
-        ask_server(&cli.host, &cli.scope, &cli.query, cli.limit, cli.threshold).await?
+        let profile = cli
+            .profile
+            .as_deref()
+            .and_then(Path::file_stem)
+            .map(|stem| stem.to_string_lossy());
+        ask_server(
+            &cli.host,
+            &cli.scope,
+            &cli.query,
+            cli.limit,
+            cli.threshold,
+            profile.as_deref(),
+            cli.explain,
+        )
+        .await
     } else {
         let index = make_index(&index_dir).await.expect("failed to build index");
         tracing::info!("index built successfully");
@@ -128,14 +173,31 @@ async fn main() -> Result<()> {
         let krates = index.crates.keys().cloned().collect();
         let scopes = ruggle_server::Scopes { sets, krates };
 
-        perform_search(
+        perform_search_with_profile(
             &index,
             &scopes,
             &cli.query,
             &cli.scope,
             Some(cli.limit),
             Some(cli.threshold),
-        )?
+            &profile,
+            cli.explain,
+        )
+        .map(|hits| {
+            hits.into_iter()
+                .map(|h| h.explained(cli.explain))
+                .collect::<Vec<_>>()
+        })
+    };
+    let hits = match hits {
+        Ok(hits) => hits,
+        Err(e) => match e.downcast_ref::<QueryParseError>() {
+            Some(parse) => {
+                eprintln!("{}", parse);
+                std::process::exit(1);
+            }
+            None => return Err(e),
+        },
     };
 
     if cli.json {
@@ -154,6 +216,26 @@ async fn main() -> Result<()> {
             h.signature,
             link
         );
+        if !h.bindings.is_empty() {
+            let bindings = h
+                .bindings
+                .iter()
+                .map(|(name, ty)| format!("?{} = {}", name, ty))
+                .collect::<Vec<_>>();
+            println!("    {}", bindings.join(", "));
+        }
+        match &h.explanation {
+            Some(explanation) => {
+                for line in explanation.to_string().lines() {
+                    println!("    {}", line);
+                }
+            }
+            None => {
+                for sim in &h.similarities().0 {
+                    println!("    {:.2} ({})", sim.score_with(&profile), sim.reason());
+                }
+            }
+        }
     }
 
     Ok(())
@@ -17,11 +17,11 @@ use axum::{
     Json, Router,
 };
 
-use ruggle_engine::search::{Hit, Scope, Set};
+use ruggle_engine::search::{ChainHit, Hit, Scope, Set};
 use ruggle_engine::types::{CrateMetadata, Item};
 use ruggle_server::{
-    index_local_crate, make_index, make_sets, perform_search, pull_crate_from_remote_index,
-    pull_set_from_remote_index, Scopes,
+    index_local_crate, make_index, make_sets, perform_chain_search, perform_search_with_profile,
+    pull_crate_from_remote_index, pull_set_from_remote_index, Scopes, SearchError,
 };
 use serde::{Deserialize, Serialize};
 use structopt::StructOpt;
@@ -32,8 +32,11 @@ use tower_http::{
     trace::TraceLayer,
 };
 
-use ruggle_engine::compare::Similarity;
-use ruggle_engine::query::parse::parse_query;
+use ruggle_engine::compare::{
+    Category, DiscreteSimilarity, ScoringProfile, Similarity, SimilarityReason,
+};
+use ruggle_engine::explain::Explanation;
+use ruggle_engine::query::parse::{parse_query_strict, QueryParseError};
 use ruggle_engine::Index;
 use ruggle_engine::Path as DocPath;
 use ruggle_engine::{build_parent_index, types};
@@ -41,12 +44,28 @@ use tracing_subscriber::fmt::format::FmtSpan;
 use tracing_subscriber::{self as ts, Layer as _};
 
 const STATIC_DIR: &str = concat!(env!("CARGO_MANIFEST_DIR"), "/static");
+const DEFAULT_PROFILE: &str = "default";
 
 struct AppState {
     index: Index,
     scopes: Scopes,
     shutdown: Arc<Notify>,
     index_dir: PathBuf,
+    /// Scoring profiles selectable per search, keyed by name.
+    profiles: HashMap<String, ScoringProfile>,
+}
+
+impl AppState {
+    /// The profile named `name`, or the default one.
+    fn profile(
+        &self,
+        name: Option<&str>,
+    ) -> Result<&ScoringProfile, (StatusCode, Json<SearchError>)> {
+        let name = name.unwrap_or(DEFAULT_PROFILE);
+        self.profiles
+            .get(name)
+            .ok_or_else(|| bad_request(&format!("unknown scoring profile `{}`", name)))
+    }
 }
 
 #[derive(Debug, Deserialize)]
@@ -55,26 +74,34 @@ struct SearchParams {
     query: Option<String>,
     limit: Option<usize>,
     threshold: Option<f32>,
+    /// Name of the scoring profile to rank with, see [`AppState::profiles`].
+    profile: Option<String>,
+    /// Whether hits come with the similarities they were ranked by.
+    #[serde(default)]
+    explain: bool,
 }
 
 async fn search_get(
     State(state): State<Arc<RwLock<AppState>>>,
     Query(params): Query<SearchParams>,
-) -> Result<Json<Vec<Hit>>, (StatusCode, String)> {
+) -> Result<Json<Vec<Hit>>, (StatusCode, Json<SearchError>)> {
     let query_str = params
         .query
         .as_deref()
-        .ok_or((StatusCode::BAD_REQUEST, "missing query".to_string()))?;
+        .ok_or_else(|| bad_request("missing query"))?;
     let state = state.read().await;
-    perform_search(
+    let profile = state.profile(params.profile.as_deref())?;
+    perform_search_with_profile(
         &state.index,
         &state.scopes,
         query_str,
         &params.scope,
         params.limit,
         params.threshold,
+        profile,
+        params.explain,
     )
-    .map(Json)
+    .map(|hits| Json(explained(hits, params.explain)))
     .map_err(|e| {
         tracing::error!("search error: {}", e);
         internal_or_bad_request(e)
@@ -85,7 +112,77 @@ async fn search_post(
     State(state): State<Arc<RwLock<AppState>>>,
     Query(mut params): Query<SearchParams>,
     body: Bytes,
-) -> Result<Json<Vec<Hit>>, (StatusCode, String)> {
+) -> Result<Json<Vec<Hit>>, (StatusCode, Json<SearchError>)> {
+    let body_str = String::from_utf8(body.to_vec()).unwrap_or_default();
+    if params.query.is_none() && !body_str.is_empty() {
+        params.query = Some(body_str);
+    }
+    let query_str = params
+        .query
+        .as_deref()
+        .ok_or_else(|| bad_request("missing query"))?;
+    let state = state.read().await;
+    let profile = state.profile(params.profile.as_deref())?;
+    perform_search_with_profile(
+        &state.index,
+        &state.scopes,
+        query_str,
+        &params.scope,
+        params.limit,
+        params.threshold,
+        profile,
+        params.explain,
+    )
+    .map(|hits| Json(explained(hits, params.explain)))
+    .map_err(internal_or_bad_request)
+}
+
+#[derive(Debug, Deserialize)]
+struct ChainSearchParams {
+    scope: String,
+    query: Option<String>,
+    limit: Option<usize>,
+    threshold: Option<f32>,
+    depth: Option<usize>,
+    /// See [`SearchParams::profile`].
+    profile: Option<String>,
+    /// See [`SearchParams::explain`].
+    #[serde(default)]
+    explain: bool,
+}
+
+async fn chain_search_get(
+    State(state): State<Arc<RwLock<AppState>>>,
+    Query(params): Query<ChainSearchParams>,
+) -> Result<Json<Vec<ChainHit>>, (StatusCode, Json<SearchError>)> {
+    let query_str = params
+        .query
+        .as_deref()
+        .ok_or_else(|| bad_request("missing query"))?;
+    let state = state.read().await;
+    let profile = state.profile(params.profile.as_deref())?;
+    perform_chain_search(
+        &state.index,
+        &state.scopes,
+        query_str,
+        &params.scope,
+        params.limit,
+        params.threshold,
+        params.depth,
+        profile,
+    )
+    .map(|chains| Json(explained_chains(chains, params.explain)))
+    .map_err(|e| {
+        tracing::error!("chain search error: {}", e);
+        internal_or_bad_request(e)
+    })
+}
+
+async fn chain_search_post(
+    State(state): State<Arc<RwLock<AppState>>>,
+    Query(mut params): Query<ChainSearchParams>,
+    body: Bytes,
+) -> Result<Json<Vec<ChainHit>>, (StatusCode, Json<SearchError>)> {
     let body_str = String::from_utf8(body.to_vec()).unwrap_or_default();
     if params.query.is_none() && !body_str.is_empty() {
         params.query = Some(body_str);
@@ -93,30 +190,67 @@ async fn search_post(
     let query_str = params
         .query
         .as_deref()
-        .ok_or((StatusCode::BAD_REQUEST, "missing query".to_string()))?;
+        .ok_or_else(|| bad_request("missing query"))?;
     let state = state.read().await;
-    perform_search(
+    let profile = state.profile(params.profile.as_deref())?;
+    perform_chain_search(
         &state.index,
         &state.scopes,
         query_str,
         &params.scope,
         params.limit,
         params.threshold,
+        params.depth,
+        profile,
     )
-    .map(Json)
+    .map(|chains| Json(explained_chains(chains, params.explain)))
     .map_err(internal_or_bad_request)
 }
 
-fn internal_or_bad_request(e: anyhow::Error) -> (StatusCode, String) {
+fn explained(hits: Vec<Hit>, explain: bool) -> Vec<Hit> {
+    hits.into_iter().map(|hit| hit.explained(explain)).collect()
+}
+
+fn explained_chains(chains: Vec<ChainHit>, explain: bool) -> Vec<ChainHit> {
+    chains
+        .into_iter()
+        .map(|chain| ChainHit {
+            hits: explained(chain.hits, explain),
+            ..chain
+        })
+        .collect()
+}
+
+fn internal_or_bad_request(e: anyhow::Error) -> (StatusCode, Json<SearchError>) {
+    let body = SearchError::from(&e);
     // Heuristically classify some errors as bad request
-    let msg = format!("{}", e);
-    if msg.contains("parsing scope") || msg.contains("parsing query") {
-        (StatusCode::BAD_REQUEST, msg)
+    if body.parse.is_some() || body.error.contains("parsing scope") {
+        (StatusCode::BAD_REQUEST, Json(body))
     } else {
-        (StatusCode::INTERNAL_SERVER_ERROR, msg)
+        (StatusCode::INTERNAL_SERVER_ERROR, Json(body))
     }
 }
 
+fn query_parse_error(query: &str, e: QueryParseError) -> (StatusCode, Json<SearchError>) {
+    (
+        StatusCode::BAD_REQUEST,
+        Json(SearchError {
+            error: format!("parsing query `{}` failed", query),
+            parse: Some(e),
+        }),
+    )
+}
+
+fn bad_request(msg: &str) -> (StatusCode, Json<SearchError>) {
+    (
+        StatusCode::BAD_REQUEST,
+        Json(SearchError {
+            error: msg.to_string(),
+            parse: None,
+        }),
+    )
+}
+
 async fn scopes_handler(State(state): State<Arc<RwLock<AppState>>>) -> Json<Vec<String>> {
     let state = state.read().await;
     let mut result = vec![];
@@ -140,6 +274,10 @@ struct Opt {
     /// Optional file path to write the selected listening URL as JSON {"url":"http://host:port"}
     #[structopt(long, name = "PORT_FILE")]
     port_file: Option<PathBuf>,
+    /// Scoring profile (TOML, or JSON with a `.json` extension) selectable with
+    /// `profile=<file stem>`, can be repeated. A profile named `default` replaces the default.
+    #[structopt(long = "profile", name = "PROFILE")]
+    profiles: Vec<PathBuf>,
 }
 
 #[tokio::main]
@@ -156,12 +294,19 @@ async fn main() {
     let sets = make_sets(Path::new(&index_dir));
     let krates = index.crates.keys().cloned().collect();
     let scopes = Scopes { sets, krates };
+    let mut profiles = HashMap::from([(DEFAULT_PROFILE.to_string(), ScoringProfile::default())]);
+    for path in &opt.profiles {
+        let profile = ScoringProfile::load(path).unwrap_or_else(|e| panic!("{:#}", e));
+        let name = path.file_stem().unwrap_or_default().to_string_lossy();
+        profiles.insert(name.to_string(), profile);
+    }
     let shutdown_notify = Arc::new(Notify::new());
     let state = Arc::new(RwLock::new(AppState {
         index,
         scopes,
         shutdown: shutdown_notify.clone(),
         index_dir: index_dir.clone(),
+        profiles,
     }));
 
     let cors = CorsLayer::new()
@@ -176,6 +321,10 @@ async fn main() {
         .route("/index", get(index_get).post(update_index))
         .route("/index/local", post(update_local_index))
         .route("/search", get(search_get).post(search_post))
+        .route(
+            "/search/chain",
+            get(chain_search_get).post(chain_search_post),
+        )
         .route("/healthz", get(healthz))
         .route("/stop", post(stop))
         .route("/scopes", get(scopes_handler))
@@ -606,15 +755,9 @@ struct DebugQueryParams {
 
 async fn debug_query_handler(
     Query(params): Query<DebugQueryParams>,
-) -> Result<Json<ruggle_engine::query::Query>, (StatusCode, String)> {
-    let parsed = parse_query(params.query.as_str())
-        .map(|(_, q)| q)
-        .map_err(|_| {
-            (
-                StatusCode::BAD_REQUEST,
-                format!("parsing query `{}` failed", params.query),
-            )
-        })?;
+) -> Result<Json<ruggle_engine::query::Query>, (StatusCode, Json<SearchError>)> {
+    let parsed = parse_query_strict(params.query.as_str())
+        .map_err(|e| query_parse_error(&params.query, e))?;
     Ok(Json(parsed))
 }
 
@@ -623,46 +766,61 @@ struct DebugSimilarityParams {
     scope: String,
     query: String,
     id: u32,
+    /// See [`SearchParams::profile`].
+    profile: Option<String>,
 }
 
 #[derive(Debug, Serialize)]
 struct PartJson {
-    discrete: Option<&'static str>,
+    discrete: Option<DiscreteSimilarity>,
     continuous: Option<f32>,
     reason: Option<String>,
+    /// The reason with its structured data, e.g. the types of a mismatch.
+    detail: SimilarityReason,
+    category: Category,
+}
+
+impl From<Similarity> for PartJson {
+    fn from(sim: Similarity) -> Self {
+        let reason = Some(sim.reason().to_string());
+        let category = sim.category();
+        let (discrete, continuous, detail) = match sim {
+            Similarity::Discrete { kind, reason, .. } => (Some(kind), None, reason),
+            Similarity::Continuous { value, reason, .. } => (None, Some(value), reason),
+        };
+        PartJson {
+            discrete,
+            continuous,
+            reason,
+            detail,
+            category,
+        }
+    }
 }
 
 #[derive(Debug, Serialize)]
 struct SimilarityJson {
     score: f32,
     parts: Vec<PartJson>,
+    /// The parts nested under the part of the query they were found comparing.
+    explanation: Option<Explanation>,
 }
 
 async fn debug_similarity_handler(
     State(state): State<Arc<RwLock<AppState>>>,
     Query(params): Query<DebugSimilarityParams>,
-) -> Result<Json<SimilarityJson>, (StatusCode, String)> {
-    let scope = Scope::try_from(params.scope.as_str()).map_err(|e| {
-        (
-            StatusCode::BAD_REQUEST,
-            format!("parsing scope `{}` failed: {}", params.scope, e),
-        )
-    })?;
-    let query = parse_query(params.query.as_str())
-        .ok()
-        .map(|(_, q)| q)
-        .ok_or((
-            StatusCode::BAD_REQUEST,
-            format!("parsing query `{}` failed", params.query),
-        ))?;
+) -> Result<Json<SimilarityJson>, (StatusCode, Json<SearchError>)> {
+    let scope = Scope::try_from(params.scope.as_str())
+        .map_err(|e| bad_request(&format!("parsing scope `{}` failed: {}", params.scope, e)))?;
+    let query = parse_query_strict(params.query.as_str())
+        .map_err(|e| query_parse_error(&params.query, e))?;
 
     let state = state.read().await;
-    let krates = state.scopes.get(&scope).map_err(|e| {
-        (
-            StatusCode::BAD_REQUEST,
-            format!("resolving scope `{}` failed: {}", params.scope, e),
-        )
-    })?;
+    let profile = state.profile(params.profile.as_deref())?;
+    let krates = state
+        .scopes
+        .get(&scope)
+        .map_err(|e| bad_request(&format!("resolving scope `{}` failed: {}", params.scope, e)))?;
 
     // Find the item by id in the given scope's crates.
     let mut found: Option<(types::Item, &types::Crate)> = None;
@@ -675,41 +833,30 @@ async fn debug_similarity_handler(
         }
     }
 
-    let (item, krate) = found.ok_or((
-        StatusCode::NOT_FOUND,
-        format!(
-            "item with id {} not found in scope {}",
-            params.id, params.scope
-        ),
-    ))?;
+    let (item, krate) = found.ok_or_else(|| {
+        (
+            StatusCode::NOT_FOUND,
+            Json(SearchError {
+                error: format!(
+                    "item with id {} not found in scope {}",
+                    params.id, params.scope
+                ),
+                parse: None,
+            }),
+        )
+    })?;
 
-    let sims = state.index.compare(&query, &item, krate, None);
-    let score = sims.score();
-    let parts = sims
-        .0
-        .into_iter()
-        .map(|s| match s {
-            Similarity::Discrete { kind, reason } => {
-                let label: &'static str = match kind {
-                    ruggle_engine::compare::DiscreteSimilarity::Equivalent => "equivalent",
-                    ruggle_engine::compare::DiscreteSimilarity::Subequal => "subequal",
-                    ruggle_engine::compare::DiscreteSimilarity::Different => "different",
-                };
-                PartJson {
-                    discrete: Some(label),
-                    continuous: None,
-                    reason: Some(reason),
-                }
-            }
-            Similarity::Continuous { value, reason } => PartJson {
-                discrete: None,
-                continuous: Some(value),
-                reason: Some(reason),
-            },
-        })
-        .collect::<Vec<_>>();
+    let (sims, explanation) = state
+        .index
+        .compare(&query, &item, krate, None, profile, true);
+    let score = sims.score_with(profile);
+    let parts = sims.0.into_iter().map(PartJson::from).collect::<Vec<_>>();
 
-    Ok(Json(SimilarityJson { score, parts }))
+    Ok(Json(SimilarityJson {
+        score,
+        parts,
+        explanation,
+    }))
 }
 
 #[derive(Debug, Deserialize)]
@@ -1071,33 +1218,25 @@ struct DebugCompareParams {
     scope: String,
     query: String,
     id: u32,
+    /// See [`SearchParams::profile`].
+    profile: Option<String>,
 }
 
 async fn debug_compare_logs_handler(
     State(state): State<Arc<RwLock<AppState>>>,
     Query(params): Query<DebugCompareParams>,
-) -> Result<Json<CompareLogsJson>, (StatusCode, String)> {
-    let scope = Scope::try_from(params.scope.as_str()).map_err(|e| {
-        (
-            StatusCode::BAD_REQUEST,
-            format!("parsing scope `{}` failed: {}", params.scope, e),
-        )
-    })?;
-    let query = parse_query(params.query.as_str())
-        .ok()
-        .map(|(_, q)| q)
-        .ok_or((
-            StatusCode::BAD_REQUEST,
-            format!("parsing query `{}` failed", params.query),
-        ))?;
+) -> Result<Json<CompareLogsJson>, (StatusCode, Json<SearchError>)> {
+    let scope = Scope::try_from(params.scope.as_str())
+        .map_err(|e| bad_request(&format!("parsing scope `{}` failed: {}", params.scope, e)))?;
+    let query = parse_query_strict(params.query.as_str())
+        .map_err(|e| query_parse_error(&params.query, e))?;
 
     let state = state.read().await;
-    let krates = state.scopes.get(&scope).map_err(|e| {
-        (
-            StatusCode::BAD_REQUEST,
-            format!("resolving scope `{}` failed: {}", params.scope, e),
-        )
-    })?;
+    let profile = state.profile(params.profile.as_deref())?;
+    let krates = state
+        .scopes
+        .get(&scope)
+        .map_err(|e| bad_request(&format!("resolving scope `{}` failed: {}", params.scope, e)))?;
 
     // Locate item by id across crates in scope
     let mut found: Option<(types::Item, &types::Crate)> = None;
@@ -1109,13 +1248,18 @@ async fn debug_compare_logs_handler(
             }
         }
     }
-    let (item, krate) = found.ok_or((
-        StatusCode::NOT_FOUND,
-        format!(
-            "item with id {} not found in scope {}",
-            params.id, params.scope
-        ),
-    ))?;
+    let (item, krate) = found.ok_or_else(|| {
+        (
+            StatusCode::NOT_FOUND,
+            Json(SearchError {
+                error: format!(
+                    "item with id {} not found in scope {}",
+                    params.id, params.scope
+                ),
+                parse: None,
+            }),
+        )
+    })?;
 
     // Capture tracing output for the comparison
     let buf = Arc::new(Mutex::new(Vec::new()));
@@ -1131,31 +1275,11 @@ async fn debug_compare_logs_handler(
         .finish();
 
     let (score, parts) = tracing::subscriber::with_default(subscriber, || {
-        let sims = state.index.compare(&query, &item, krate, None);
-        let score = sims.score();
-        let parts = sims
-            .0
-            .into_iter()
-            .map(|s| match s {
-                Similarity::Discrete { kind, reason } => {
-                    let label: &'static str = match kind {
-                        ruggle_engine::compare::DiscreteSimilarity::Equivalent => "equivalent",
-                        ruggle_engine::compare::DiscreteSimilarity::Subequal => "subequal",
-                        ruggle_engine::compare::DiscreteSimilarity::Different => "different",
-                    };
-                    PartJson {
-                        discrete: Some(label),
-                        continuous: None,
-                        reason: Some(reason),
-                    }
-                }
-                Similarity::Continuous { value, reason } => PartJson {
-                    discrete: None,
-                    continuous: Some(value),
-                    reason: Some(reason),
-                },
-            })
-            .collect::<Vec<_>>();
+        let (sims, _) = state
+            .index
+            .compare(&query, &item, krate, None, profile, false);
+        let score = sims.score_with(profile);
+        let parts = sims.0.into_iter().map(PartJson::from).collect::<Vec<_>>();
         (score, parts)
     });
 
@@ -11,14 +11,15 @@ use guppy::{graph::PackageGraph, MetadataCommand};
 use rayon::iter::{IntoParallelRefIterator as _, ParallelIterator as _};
 use ruggle_engine::{
     build_parent_index,
-    query::parse::parse_query,
-    search::{Hit, Scope, Set},
+    compare::ScoringProfile,
+    query::parse::{parse_chain_query_strict, parse_query_expr_strict, QueryParseError},
+    search::{ChainHit, Hit, Scope, Set},
     types::{self, Crate, CrateMetadata},
     Index, Parent,
 };
 use ruggle_util::shake;
 
-use serde::Deserialize as _;
+use serde::{Deserialize, Serialize};
 use std::io::Read;
 use tokio::{fs::OpenOptions, process::Command};
 use tokio::{
@@ -27,6 +28,24 @@ use tokio::{
 };
 use tracing::{debug, error, info, warn};
 
+/// Error body returned by the search endpoints.
+#[derive(Debug, Serialize, Deserialize)]
+pub struct SearchError {
+    pub error: String,
+    /// Where and why the query failed to parse, if that is what went wrong.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub parse: Option<QueryParseError>,
+}
+
+impl From<&anyhow::Error> for SearchError {
+    fn from(e: &anyhow::Error) -> Self {
+        SearchError {
+            error: e.to_string(),
+            parse: e.downcast_ref::<QueryParseError>().cloned(),
+        }
+    }
+}
+
 pub fn perform_search(
     index: &Index,
     scopes: &Scopes,
@@ -34,6 +53,31 @@ pub fn perform_search(
     scope_str: &str,
     limit: Option<usize>,
     threshold: Option<f32>,
+) -> anyhow::Result<Vec<Hit>> {
+    perform_search_with_profile(
+        index,
+        scopes,
+        query_str,
+        scope_str,
+        limit,
+        threshold,
+        &ScoringProfile::default(),
+        false,
+    )
+}
+
+/// Same as [`perform_search`], ranking hits with the weights of `profile` and explaining them if
+/// `explain`.
+#[allow(clippy::too_many_arguments)]
+pub fn perform_search_with_profile(
+    index: &Index,
+    scopes: &Scopes,
+    query_str: &str,
+    scope_str: &str,
+    limit: Option<usize>,
+    threshold: Option<f32>,
+    profile: &ScoringProfile,
+    explain: bool,
 ) -> anyhow::Result<Vec<Hit>> {
     tracing::info!(
         "performing search for query `{}` in scope `{}`",
@@ -47,10 +91,8 @@ pub fn perform_search(
         Scope::try_from(scope_str).context(format!("parsing scope `{}` failed", scope_str))?;
     debug!(?scope);
 
-    let query = parse_query(query_str)
-        .ok()
-        .context(format!("parsing query `{}` failed", query_str))?
-        .1;
+    let query = parse_query_expr_strict(query_str)
+        .with_context(|| format!("parsing query `{}` failed", query_str))?;
     debug!(?query);
 
     let limit = limit.unwrap_or(30);
@@ -58,17 +100,55 @@ pub fn perform_search(
     let krates = scopes.get(&scope)?;
 
     let hits = index
-        .search(&query, &krates, threshold)
+        .search(&query, &krates, threshold, profile, explain)
         .with_context(|| format!("search with query `{:?}` failed", query))?;
     let hits = hits
         .into_iter()
-        .inspect(|hit| debug!(?hit.name, link = ?hit.link, similarities = ?hit.similarities(), score = ?hit.similarities().score()))
+        .inspect(|hit| debug!(?hit.name, link = ?hit.link, similarities = ?hit.similarities(), score = ?hit.similarities().score_with(profile)))
         .take(limit)
         .collect::<Vec<_>>();
 
     Ok(hits)
 }
 
+/// Search chains of functions answering `query_str`, ranking links with the weights of `profile`.
+#[allow(clippy::too_many_arguments)]
+pub fn perform_chain_search(
+    index: &Index,
+    scopes: &Scopes,
+    query_str: &str,
+    scope_str: &str,
+    limit: Option<usize>,
+    threshold: Option<f32>,
+    depth: Option<usize>,
+    profile: &ScoringProfile,
+) -> anyhow::Result<Vec<ChainHit>> {
+    tracing::info!(
+        "performing chain search for query `{}` in scope `{}`",
+        query_str,
+        scope_str
+    );
+
+    let scope =
+        Scope::try_from(scope_str).context(format!("parsing scope `{}` failed", scope_str))?;
+    debug!(?scope);
+
+    let query = parse_chain_query_strict(query_str)
+        .with_context(|| format!("parsing query `{}` failed", query_str))?;
+    debug!(?query);
+
+    let limit = limit.unwrap_or(30);
+    let threshold = threshold.unwrap_or(0.4);
+    let depth = depth.unwrap_or(3);
+    let krates = scopes.get(&scope)?;
+
+    let chains = index
+        .search_chain(&query, &krates, threshold, depth, profile)
+        .with_context(|| format!("chain search with query `{}` failed", query))?;
+
+    Ok(chains.into_iter().take(limit).collect())
+}
+
 pub async fn make_index(index_dir: &Path) -> Result<Index> {
     let crate_dir = index_dir.join("crate");
     info!("building index from {}", crate_dir.display());
@@ -410,6 +490,35 @@ mod tests {
         let result = pull_crate_from_docs_rs(&krate).await;
         assert!(result.is_ok());
     }
+
+    #[test]
+    fn search_errors_carry_parse_errors() {
+        let index = Index {
+            crates: HashMap::new(),
+            parents: HashMap::new(),
+        };
+        let scopes = Scopes {
+            sets: HashMap::new(),
+            krates: HashSet::new(),
+        };
+        let err = perform_search(
+            &index,
+            &scopes,
+            "fn (Vec<u8>) -> ",
+            "crate:test:0.0.1",
+            None,
+            None,
+        )
+        .expect_err("search should fail");
+        let body = SearchError::from(&err);
+        let parse = body.parse.expect("expected a parse error");
+        assert_eq!(parse.offset, 16);
+        assert_eq!(parse.expected, vec!["type".to_string()]);
+        assert!(body.error.contains("parsing query"));
+
+        let json = serde_json::to_value(SearchError::from(&err)).unwrap();
+        assert_eq!(json["parse"]["offset"], 16);
+    }
 }
 
 pub async fn pull_crate_from_remote_index(
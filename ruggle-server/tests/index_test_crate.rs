@@ -3,8 +3,19 @@ use std::fs;
 use std::path::{Path, PathBuf};
 use std::process::Command;
 
+use tokio::sync::OnceCell;
+
+use ruggle_engine::compare::{
+    Category, DiscreteSimilarity, ScoringProfile, Similarity, SimilarityReason,
+};
+use ruggle_engine::explain::Explanation;
+use ruggle_engine::names::NameMatching;
+use ruggle_engine::search::Hit;
 use ruggle_engine::types::CrateMetadata;
-use ruggle_server::{make_index, perform_search, Scopes};
+use ruggle_engine::Index;
+use ruggle_server::{
+    make_index, perform_chain_search, perform_search, perform_search_with_profile, Scopes,
+};
 use tracing::Level;
 
 fn workspace_path(parts: &[&str]) -> PathBuf {
@@ -34,8 +45,16 @@ fn find_crate_json(crate_dir: &Path, crate_name: &str) -> PathBuf {
     json
 }
 
-#[tokio::test]
-async fn index_local_test_crate_and_query() {
+/// The local `test` crate, indexed once and shared by every test.
+static TEST_CRATE: OnceCell<(Index, Scopes, String)> = OnceCell::const_new();
+
+/// Builds rustdoc JSON for the local `test` crate and indexes it, returning the index, scopes
+/// containing only `test`, and its scope string.
+async fn index_test_crate() -> &'static (Index, Scopes, String) {
+    TEST_CRATE.get_or_init(build_test_crate_index).await
+}
+
+async fn build_test_crate_index() -> (Index, Scopes, String) {
     // Initialize logging for debugging if needed
     let _ = tracing_subscriber::fmt::fmt()
         .with_max_level(Level::TRACE)
@@ -55,16 +74,17 @@ async fn index_local_test_crate_and_query() {
     run_rustdoc_json(&test_crate_dir);
 
     // 2) Prepare a temporary index directory layout: <tmp>/crate/test.json
-    let tmp_root =
-        std::env::temp_dir().join(format!("ruggle_server_index_test_{}", std::process::id()));
-    let crate_dir = tmp_root.join("crate");
+    let tmp_root = tempfile::tempdir().expect("failed to create temp index dir");
+    let crate_dir = tmp_root.path().join("crate");
     fs::create_dir_all(&crate_dir).expect("failed to create temp index dir");
     let src_json = find_crate_json(&workspace_root, "test");
     let dst_json = crate_dir.join("test.json");
     fs::copy(&src_json, &dst_json).expect("failed to copy rustdoc json into index dir");
 
-    // 3) Build an Index from the temp directory
-    let index = make_index(&tmp_root).await.expect("make_index failed");
+    // 3) Build an Index from the temp directory, which is removed once loaded
+    let index = make_index(tmp_root.path())
+        .await
+        .expect("make_index failed");
 
     // 4) Build Scopes for the crate `test`
     let mut scopes = Scopes {
@@ -84,14 +104,26 @@ async fn index_local_test_crate_and_query() {
         tracing::info!("krate: {}", krate);
     }
 
-    // 5) Run a simple query that should match a known function in `test`
-    // e.g., `util::text::split_words`
     let scope_str = format!("crate:{}:{}", test_meta.name, test_meta.version);
+    (index, scopes, scope_str)
+}
+
+/// The score `perform_search` ranked `hit` with, under the default profile.
+fn ranked(hit: &Hit) -> f32 {
+    hit.similarities().score_with(&ScoringProfile::default())
+}
+
+#[tokio::test]
+async fn index_local_test_crate_and_query() {
+    let (index, scopes, scope_str) = index_test_crate().await;
+
+    // Run a simple query that should match a known function in `test`
+    // e.g., `util::text::split_words`
     let hits = perform_search(
-        &index,
-        &scopes,
+        index,
+        scopes,
         "fn split_words(&str) -> Vec<String>",
-        &scope_str,
+        scope_str,
         Some(20),
         Some(0.4),
     )
@@ -105,3 +137,576 @@ async fn index_local_test_crate_and_query() {
         hits.iter().map(|h| h.name.clone()).collect::<Vec<_>>()
     );
 }
+
+#[tokio::test]
+async fn chain_query_connects_user_to_words() {
+    let (index, scopes, scope_str) = index_test_crate().await;
+
+    // `User::name_ref` gives a `&str`, which `split_words` turns into `Vec<String>`.
+    let chains = perform_chain_search(
+        index,
+        scopes,
+        "User -> ... -> Vec<String>",
+        scope_str,
+        Some(20),
+        Some(0.4),
+        Some(2),
+        &ScoringProfile::default(),
+    )
+    .expect("chain search failed");
+
+    let names = chains
+        .iter()
+        .map(|c| c.hits.iter().map(|h| h.name.clone()).collect::<Vec<_>>())
+        .collect::<Vec<_>>();
+    tracing::info!("chains: {:?}", names);
+
+    assert!(
+        names.contains(&vec!["name_ref".to_string(), "split_words".to_string()]),
+        "expected name_ref -> split_words, got: {:?}",
+        names
+    );
+}
+
+#[tokio::test]
+async fn scoped_query_only_considers_owner() {
+    let (index, scopes, scope_str) = index_test_crate().await;
+    let names = |query: &str| {
+        perform_search(index, scopes, query, scope_str, Some(20), Some(0.4))
+            .expect("search failed")
+            .into_iter()
+            .map(|h| h.name)
+            .collect::<Vec<_>>()
+    };
+
+    let on_user = names("struct User: fn(..) -> &str");
+    tracing::info!("hits on User: {:?}", on_user);
+    assert!(on_user.contains(&"name_ref".to_string()));
+    assert!(!on_user.contains(&"split_words".to_string()));
+
+    let in_text = names("mod text: fn(&str) -> Vec<String>");
+    tracing::info!("hits in text: {:?}", in_text);
+    assert!(in_text.contains(&"split_words".to_string()));
+    assert!(!in_text.contains(&"name_ref".to_string()));
+}
+
+#[tokio::test]
+async fn macro_queries_find_macro_rules() {
+    let (index, scopes, scope_str) = index_test_crate().await;
+    let hits = |query: &str| {
+        perform_search(index, scopes, query, scope_str, Some(20), Some(0.4)).expect("search failed")
+    };
+
+    let by_name = hits("macro make_vec!");
+    tracing::info!("hits for make_vec!: {:?}", by_name);
+    let make_vec = by_name
+        .first()
+        .expect("expected to find make_vec, got no hits");
+    assert_eq!(make_vec.name, "make_vec");
+    assert!(make_vec.link.ends_with("macro.make_vec.html"));
+
+    let by_fragments = hits("macro (ident, ty)");
+    tracing::info!("hits for (ident, ty): {:?}", by_fragments);
+    assert_eq!(
+        by_fragments.first().map(|h| h.name.as_str()),
+        Some("define_newtype")
+    );
+}
+
+#[tokio::test]
+async fn type_def_queries_find_type_definitions() {
+    let (index, scopes, scope_str) = index_test_crate().await;
+    let top = |query: &str| {
+        let hits = perform_search(index, scopes, query, scope_str, Some(20), Some(0.4))
+            .expect("search failed");
+        tracing::info!("hits for {}: {:?}", query, hits);
+        hits.into_iter()
+            .next()
+            .unwrap_or_else(|| panic!("no hits for {}", query))
+    };
+
+    let wrapper = top("struct Wrapper<_>");
+    assert_eq!(wrapper.name, "Wrapper");
+    assert!(wrapper.link.ends_with("struct.Wrapper.html"));
+
+    let bounded = top("struct BoundVec<_, _>");
+    assert_eq!(bounded.name, "BoundedVec");
+
+    let repository = top("trait Repository");
+    assert!(repository.link.ends_with("trait.Repository.html"));
+
+    let user_id = top("type _ = u64");
+    assert_eq!(user_id.name, "UserId");
+    assert!(user_id.link.ends_with("type.UserId.html"));
+}
+
+#[tokio::test]
+async fn trait_methods_are_annotated_with_their_trait() {
+    let (index, scopes, scope_str) = index_test_crate().await;
+    let hits = perform_search(index, scopes, "fn insert", scope_str, Some(20), Some(0.4))
+        .expect("search failed");
+    tracing::info!("hits: {:?}", hits);
+
+    let inserts: Vec<_> = hits.iter().filter(|h| h.name == "insert").collect();
+    // The declaration in `Repository` and its implementation for `InMemoryRepo`.
+    assert_eq!(inserts.len(), 2, "hits: {:?}", hits);
+    assert!(inserts
+        .iter()
+        .all(|h| h.trait_.as_deref() == Some("Repository")));
+    assert!(inserts
+        .iter()
+        .any(|h| h.link.ends_with("trait.Repository.html#tymethod.insert")));
+
+    let hits = perform_search(index, scopes, "fn name_ref", scope_str, Some(20), Some(0.4))
+        .expect("search failed");
+    let name_refs: Vec<_> = hits.iter().filter(|h| h.name == "name_ref").collect();
+    assert_eq!(name_refs.len(), 1, "hits: {:?}", hits);
+    assert_eq!(name_refs[0].trait_, None);
+}
+
+#[tokio::test]
+async fn projection_queries_match_associated_types() {
+    let (index, scopes, scope_str) = index_test_crate().await;
+    let hits = perform_search(
+        index,
+        scopes,
+        "fn (&Self, Self::Id) -> Option<&Self::Item>",
+        scope_str,
+        Some(20),
+        Some(0.4),
+    )
+    .expect("search failed");
+    tracing::info!("hits: {:?}", hits);
+
+    let top = hits.first().expect("no hits");
+    assert_eq!(top.name, "get");
+    assert_eq!(top.trait_.as_deref(), Some("Repository"));
+}
+
+#[tokio::test]
+async fn qualified_queries_only_match_the_intended_item() {
+    let (index, scopes, scope_str) = index_test_crate().await;
+    let names = |query: &str| {
+        let hits = perform_search(index, scopes, query, scope_str, Some(20), Some(0.4))
+            .expect("search failed");
+        tracing::info!("hits for {}: {:?}", query, hits);
+        hits.into_iter().map(|h| h.name).collect::<Vec<_>>()
+    };
+
+    assert_eq!(
+        names("fn text::split_words").first().map(String::as_str),
+        Some("split_words")
+    );
+    assert!(!names("fn math::split_words").contains(&"split_words".to_string()));
+
+    assert_eq!(
+        names("fn (&user::User) -> &str")
+            .first()
+            .map(String::as_str),
+        Some("name_ref")
+    );
+    assert!(!names("fn (&order::User) -> &str").contains(&"name_ref".to_string()));
+}
+
+#[tokio::test]
+async fn const_generics_and_arrays_unify() {
+    let (index, scopes, scope_str) = index_test_crate().await;
+    let search = |query: &str| {
+        let hits = perform_search(index, scopes, query, scope_str, Some(20), Some(0.4))
+            .expect("search failed");
+        tracing::info!("hits for {}: {:?}", query, hits);
+        hits
+    };
+    let score = |query: &str, name: &str| {
+        search(query)
+            .into_iter()
+            .find(|h| h.name == name)
+            .map(|h| ranked(&h))
+            .unwrap_or_else(|| panic!("{} not found for {}", name, query))
+    };
+
+    // `AsRef::as_ref` has the same signature, so `as_slice` may tie with it.
+    let as_slice = search("fn (&BoundedVec<T, N>) -> &[T]");
+    let best = ranked(&as_slice[0]);
+    assert!(as_slice
+        .iter()
+        .take_while(|h| ranked(h) == best)
+        .any(|h| h.name == "as_slice"));
+
+    let matmul = "fn ([[T; K]; R], [[T; C]; K]) -> [[T; C]; R]";
+    assert_eq!(
+        search(matmul).first().map(|h| h.name.as_str()),
+        Some("matmul")
+    );
+    assert!(
+        score(matmul, "matmul") < score("fn ([[T; K]; R], [[T; C]; K]) -> [[T; R]; C]", "matmul")
+    );
+    assert!(
+        score(matmul, "matmul") < score("fn ([[T; 2]; 2], [[T; 2]; 2]) -> [[T; 2]; 2]", "matmul")
+    );
+}
+
+#[tokio::test]
+async fn boolean_queries_and_filters() {
+    let (index, scopes, scope_str) = index_test_crate().await;
+    let names = |query: &str| {
+        let hits = perform_search(index, scopes, query, scope_str, Some(50), Some(0.4))
+            .expect("search failed");
+        tracing::info!("hits for {}: {:?}", query, hits);
+        hits.into_iter().map(|h| h.name).collect::<Vec<_>>()
+    };
+
+    let either = names("fn split_words | fn join_words");
+    assert!(either.contains(&"split_words".to_string()));
+    assert!(either.contains(&"join_words".to_string()));
+
+    let add = "fn (u32, u32) -> u32";
+    assert_eq!(names(&format!("{} & is:async", add)), vec!["async_add"]);
+    assert!(!names(&format!("{} & !is:async", add)).contains(&"async_add".to_string()));
+
+    assert!(names("fn pop & kind:method").contains(&"pop".to_string()));
+    assert!(!names("fn pop & kind:fn").contains(&"pop".to_string()));
+
+    let math = names("in:util::math & kind:fn");
+    assert!(math.contains(&"dot".to_string()) && math.contains(&"matmul".to_string()));
+    assert!(!math.contains(&"split_words".to_string()));
+
+    assert!(!names("crate:test & kind:struct").is_empty());
+    assert!(names("crate:serde & kind:struct").is_empty());
+
+    assert_eq!(names("in:text & deprecated:yes"), vec!["word_count"]);
+    assert!(!names("in:text & deprecated:no").contains(&"word_count".to_string()));
+}
+
+#[tokio::test]
+async fn named_holes_bind_consistently() {
+    let (index, scopes, scope_str) = index_test_crate().await;
+    let search = |query: &str| {
+        let hits = perform_search(index, scopes, query, scope_str, Some(20), Some(0.4))
+            .expect("search failed");
+        tracing::info!("hits for {}: {:?}", query, hits);
+        hits
+    };
+
+    let hits = search("fn (&Graph<?e>, ?a, ?a) -> bool");
+    let path_exists = hits.first().expect("expected a hit");
+    assert_eq!(path_exists.name, "path_exists");
+    assert_eq!(
+        path_exists.bindings.get("a").map(String::as_str),
+        Some("NodeId")
+    );
+    assert_eq!(path_exists.bindings.get("e").map(String::as_str), Some("E"));
+
+    let score = |query: &str| {
+        search(query)
+            .into_iter()
+            .find(|h| h.name == "add_edge")
+            .map(|h| ranked(&h))
+            .unwrap_or(f32::INFINITY)
+    };
+    assert!(
+        score("fn add_edge(&mut Graph<?e>, ?a, ?a, ?e)")
+            < score("fn add_edge(&mut Graph<?e>, ?a, ?a, ?a)")
+    );
+}
+
+#[tokio::test]
+async fn unordered_arguments_match_in_any_order() {
+    let (index, scopes, scope_str) = index_test_crate().await;
+    let neighbors = |query: &str| {
+        perform_search(index, scopes, query, scope_str, Some(20), Some(0.4))
+            .expect("search failed")
+            .into_iter()
+            .find(|h| h.name == "neighbors")
+    };
+
+    let hit = neighbors("fn {NodeId, &Graph<E>} -> &Vec<(NodeId, E)>")
+        .expect("expected neighbors to match in any order");
+    let reasons = hit
+        .similarities()
+        .0
+        .iter()
+        .map(Similarity::reason)
+        .collect::<Vec<_>>();
+    assert!(
+        reasons.contains(&&SimilarityReason::ArgumentsReordered(vec![
+            Some(1),
+            Some(0)
+        ])),
+        "{:?}",
+        reasons
+    );
+
+    // Arguments in order are not penalized, and in parentheses they must be in order.
+    let in_order = neighbors("fn {&Graph<E>, NodeId} -> &Vec<(NodeId, E)>").unwrap();
+    assert!(ranked(&in_order) < ranked(&hit));
+    assert!(neighbors("fn (NodeId, &Graph<E>) -> &Vec<(NodeId, E)>")
+        .is_none_or(|h| ranked(&h) > ranked(&hit)));
+}
+
+#[tokio::test]
+async fn receivers_match_methods_taking_self() {
+    let (index, scopes, scope_str) = index_test_crate().await;
+    let search = |query: &str| {
+        perform_search(index, scopes, query, scope_str, Some(50), Some(0.4)).expect("search failed")
+    };
+    let score = |query: &str, name: &str| {
+        search(query)
+            .into_iter()
+            .find(|h| h.name == name)
+            .map(|h| ranked(&h))
+    };
+
+    let by_mut = score("fn (&mut self) -> Option<T>", "pop").expect("expected pop to match");
+    let by_ref = score("fn (&self) -> Option<T>", "pop");
+    assert!(
+        by_ref.is_none_or(|s| s > by_mut),
+        "{:?} vs {}",
+        by_ref,
+        by_mut
+    );
+
+    // The receiver type is checked against the type the method is implemented for.
+    let hits = search("BoundedVec<T, N>.?(&self) -> &[T]");
+    let best = ranked(&hits[0]);
+    assert!(hits
+        .iter()
+        .take_while(|h| ranked(h) == best)
+        .any(|h| h.name == "as_slice"));
+    let on_bounded = score("BoundedVec<T, N>.as_slice(&self) -> &[T]", "as_slice").unwrap();
+    let on_wrapper = score("Wrapper<T>.as_slice(&self) -> &[T]", "as_slice");
+    assert!(on_wrapper.is_none_or(|s| s > on_bounded));
+    assert!(score("BoundedVec<T, N>.push(T)", "push").is_some());
+}
+
+#[tokio::test]
+async fn type_aliases_match_what_they_stand_for() {
+    let (index, scopes, scope_str) = index_test_crate().await;
+    let score = |query: &str, name: &str| {
+        perform_search(index, scopes, query, scope_str, Some(50), Some(0.4))
+            .expect("search failed")
+            .into_iter()
+            .find(|h| h.name == name)
+            .map(|h| ranked(&h))
+    };
+
+    // `UserId` is an alias of `u64`.
+    assert_eq!(score("fn (&Order) -> u64", "buyer_id"), Some(0.0));
+    assert_eq!(score("fn (&Order) -> UserId", "buyer_id"), Some(0.0));
+    // `Result<T>` is an alias of `Result<T, TestError>`, with a defaulted error type.
+    assert_eq!(
+        score("fn (&str) -> Result<String, TestError>", "parse_word"),
+        Some(0.0)
+    );
+    assert_eq!(
+        score("fn (&str) -> Result<String>", "parse_word"),
+        Some(0.0)
+    );
+    let wrong_error = score("fn (&str) -> Result<String, io::Error>", "parse_word");
+    assert!(wrong_error.is_none_or(|s| s > 0.0));
+}
+
+#[tokio::test]
+async fn coercible_types_match_as_subequal() {
+    let (index, scopes, scope_str) = index_test_crate().await;
+    let hit = |query: &str, name: &str| {
+        perform_search(index, scopes, query, scope_str, Some(50), Some(0.4))
+            .expect("search failed")
+            .into_iter()
+            .find(|h| h.name == name)
+    };
+    let reasons = |hit: &Hit| {
+        hit.similarities()
+            .0
+            .iter()
+            .map(|sim| sim.reason().clone())
+            .collect::<Vec<_>>()
+    };
+
+    // `Vec<T>` derefs to `[T]` by the std table.
+    let neighbors = hit("fn (&Graph<E>, NodeId) -> &[(NodeId, E)]", "neighbors")
+        .expect("expected neighbors to match through deref");
+    assert!(reasons(&neighbors).contains(&SimilarityReason::DerefCoercion));
+    // And the query side coerces too, `String` derefs to `str`.
+    let parse_word = hit("fn (&String) -> Result<String>", "parse_word")
+        .expect("expected parse_word to match through deref");
+    assert!(reasons(&parse_word).contains(&SimilarityReason::DerefCoercion));
+
+    // Impls in the index are consulted for candidate types.
+    let bounded_sum =
+        hit("fn (&[T]) -> T", "bounded_sum").expect("expected bounded_sum to match through AsRef");
+    assert!(reasons(&bounded_sum).contains(&SimilarityReason::AsRefConversion));
+    let shout =
+        hit("fn (&String) -> String", "shout").expect("expected shout to match through Deref");
+    assert!(
+        reasons(&shout).contains(&SimilarityReason::DerefCoercion),
+        "{:?}",
+        reasons(&shout)
+    );
+    assert!(ranked(&shout) > 0.0);
+}
+
+#[tokio::test]
+async fn generics_are_substituted_only_by_types_meeting_their_bounds() {
+    let (index, scopes, scope_str) = index_test_crate().await;
+    let hit = |query: &str, name: &str| {
+        perform_search(index, scopes, query, scope_str, Some(50), Some(1.0))
+            .expect("search failed")
+            .into_iter()
+            .find(|h| h.name == name)
+            .unwrap_or_else(|| panic!("{} not found for {}", name, query))
+    };
+    let has_reason = |hit: &Hit, reason: &str| {
+        hit.similarities()
+            .0
+            .iter()
+            .any(|sim| sim.reason().to_string() == reason)
+    };
+
+    // `Identifiable` is implemented in the crate, for `User` only.
+    let user = hit("fn (&User) -> String", "describe");
+    assert!(has_reason(&user, "generic substituted"));
+    let number = hit("fn (&u32) -> String", "describe");
+    assert!(has_reason(
+        &number,
+        "`u32` doesn't implement `Identifiable`"
+    ));
+    assert!(ranked(&number) > ranked(&user));
+
+    // `Read` is from std, whose implementors are known by name.
+    let file = hit("fn (File) -> io::Result<Vec<String>>", "read_words");
+    assert!(has_reason(&file, "generic substituted"));
+    let number = hit("fn (u32) -> io::Result<Vec<String>>", "read_words");
+    assert!(ranked(&number) > ranked(&file));
+}
+
+#[tokio::test]
+async fn scoring_profiles_reweight_hits() {
+    let (index, scopes, scope_str) = index_test_crate().await;
+    let best = |profile: &ScoringProfile| {
+        perform_search_with_profile(
+            index,
+            scopes,
+            "fn word_count(&str) -> Vec<String>",
+            scope_str,
+            Some(50),
+            Some(1.0),
+            profile,
+            false,
+        )
+        .expect("search failed")
+        .first()
+        .map(|h| h.name.clone())
+    };
+
+    // Types outweigh the name by default.
+    assert_eq!(
+        best(&ScoringProfile::default()).as_deref(),
+        Some("split_words")
+    );
+
+    // Weighing names up lets the name win.
+    let profile = ScoringProfile {
+        name: 4.0,
+        ..Default::default()
+    };
+    assert_eq!(best(&profile).as_deref(), Some("word_count"));
+}
+
+#[tokio::test]
+async fn missing_arguments_are_reported() {
+    let (index, scopes, scope_str) = index_test_crate().await;
+    let hit = perform_search(
+        index,
+        scopes,
+        "fn (&str, usize) -> usize",
+        scope_str,
+        Some(20),
+        Some(0.4),
+    )
+    .expect("search failed")
+    .into_iter()
+    .find(|h| h.name == "word_count")
+    .expect("expected word_count to match");
+    assert!(hit.similarities().0.contains(&Similarity::Discrete {
+        kind: DiscreteSimilarity::Different,
+        reason: SimilarityReason::MissingArgument { index: 1 },
+        category: Category::Arity,
+    }));
+}
+
+fn child<'a>(node: &'a Explanation, label: &str) -> &'a Explanation {
+    node.children
+        .iter()
+        .find(|c| c.label.starts_with(label))
+        .unwrap_or_else(|| panic!("expected `{}` under `{}`", label, node.label))
+}
+
+#[tokio::test]
+async fn explanations_mirror_the_query() {
+    let (index, scopes, scope_str) = index_test_crate().await;
+    let query = "fn (&str) -> Vec<u8>";
+    let profile = ScoringProfile::default();
+    let hit = perform_search_with_profile(
+        index,
+        scopes,
+        query,
+        scope_str,
+        Some(20),
+        Some(0.4),
+        &profile,
+        true,
+    )
+    .expect("search failed")
+    .into_iter()
+    .find(|h| h.name == "split_words")
+    .expect("expected split_words to match");
+    let tree = hit
+        .explanation
+        .as_ref()
+        .expect("expected a function query to be explained");
+    println!("{}", tree);
+
+    assert_eq!(tree.label, "fn(&str) -> Vec<u8>");
+    assert_eq!(tree.score, Some(hit.similarities().score_with(&profile)));
+    assert_eq!(child(tree, "argument 0").score, Some(0.0));
+
+    // `u8` differs from `String`, below the matching `Vec`.
+    let ret = child(child(tree, "return type"), "`Vec<u8>`");
+    assert!(ret.score.unwrap() > 0.0);
+    assert!(ret.similarities.iter().all(|sim| sim.score == 0.0));
+    assert!(child(ret, "generic arg 0").score.unwrap() > 0.0);
+}
+
+#[tokio::test]
+async fn names_match_by_tokens() {
+    let (index, scopes, scope_str) = index_test_crate().await;
+    let hits = |query: &str, profile: &ScoringProfile| {
+        perform_search_with_profile(
+            index,
+            scopes,
+            query,
+            scope_str,
+            Some(20),
+            Some(0.4),
+            profile,
+            false,
+        )
+        .expect("search failed")
+        .into_iter()
+        .map(|h| h.name)
+        .collect::<Vec<_>>()
+    };
+
+    let tokenized = ScoringProfile::default();
+    assert_eq!(hits("fn split", &tokenized), ["split_words"]);
+    assert!(hits("fn len", &tokenized).contains(&"word_count".into()));
+
+    // Edit distance is still available, and knows neither tokens nor synonyms.
+    let levenshtein = ScoringProfile {
+        names: NameMatching::Levenshtein,
+        ..Default::default()
+    };
+    assert!(hits("fn split", &levenshtein).is_empty());
+    assert!(!hits("fn len", &levenshtein).contains(&"word_count".into()));
+}
@@ -28,6 +28,11 @@ impl User {
     pub fn rename(&mut self, new_name: impl Into<String>) { self.name = new_name.into(); }
 }
 
+/// Describe an identifiable item by its id.
+pub fn describe<T: Identifiable>(item: &T) -> String {
+    format!("#{:?}", item.id())
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
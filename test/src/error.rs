@@ -11,3 +11,6 @@ pub enum TestError {
 }
 
 
+
+/// Result of fallible operations in this crate.
+pub type Result<T, E = TestError> = std::result::Result<T, E>;
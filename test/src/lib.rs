@@ -2,6 +2,9 @@
 //! Rust features (modules, generics, traits, const generics, macros, errors,
 //! visibility, re-exports) to act as a robust test target.
 
+// Items are kept as written, since changing them changes what gets indexed.
+#![allow(clippy::new_without_default, clippy::bool_assert_comparison)]
+
 pub mod alg;
 pub mod domain;
 pub mod error;
@@ -5,6 +5,12 @@ pub type UserId = u64;
 #[derive(Debug, Clone, PartialEq, Eq, PartialOrd, Ord, Hash)]
 pub struct Wrapper<T>(pub T);
 
+impl<T> std::ops::Deref for Wrapper<T> {
+    type Target = T;
+
+    fn deref(&self) -> &T { &self.0 }
+}
+
 /// Vector with a compile-time maximum capacity `N`.
 #[derive(Debug, Clone, PartialEq, Eq)]
 pub struct BoundedVec<T, const N: usize> {
@@ -25,6 +31,10 @@ impl<T, const N: usize> BoundedVec<T, N> {
     pub fn as_slice(&self) -> &[T] { &self.inner }
 }
 
+impl<T, const N: usize> AsRef<[T]> for BoundedVec<T, N> {
+    fn as_ref(&self) -> &[T] { &self.inner }
+}
+
 pub trait Repository {
     type Id: Copy + Eq;
     type Item;
@@ -1,5 +1,7 @@
 use std::ops::{Add, Mul};
 
+use crate::types::BoundedVec;
+
 /// Trait bound grouping for numeric-like types that support addition and multiplication.
 pub trait Numeric:
     Copy + Clone + Add<Output = Self> + Mul<Output = Self> + Default
@@ -20,6 +22,11 @@ pub fn dot<T: Numeric>(left: &[T], right: &[T]) -> Option<T> {
     Some(acc)
 }
 
+/// Sum the values held in a bounded vector.
+pub fn bounded_sum<T: Numeric, const N: usize>(values: &BoundedVec<T, N>) -> T {
+    values.as_ref().iter().copied().fold(T::default(), |acc, v| acc + v)
+}
+
 /// Fixed-size matrix multiplication using const generics (C = A x B).
 pub fn matmul<T: Numeric, const R: usize, const C: usize, const K: usize>(
     a: [[T; K]; R],
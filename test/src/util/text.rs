@@ -1,5 +1,7 @@
 use once_cell::sync::Lazy;
 
+use crate::types::Wrapper;
+
 static DELIMS: Lazy<[char; 6]> = Lazy::new(|| [' ', '\n', '\t', ',', ';', '.']);
 
 /// Split a string into lowercase words using a small set of delimiters.
@@ -11,6 +13,32 @@ pub fn split_words(input: &str) -> Vec<String> {
         .collect()
 }
 
+/// Count the words of a string.
+#[deprecated(since = "0.1.0", note = "use `split_words(input).len()` instead")]
+pub fn word_count(input: &str) -> usize {
+    split_words(input).len()
+}
+
+/// Parse input holding exactly one word.
+pub fn parse_word(input: &str) -> crate::error::Result<String> {
+    match split_words(input).as_slice() {
+        [word] => Ok(word.clone()),
+        _ => Err(crate::TestError::Parse(input.to_string())),
+    }
+}
+
+/// Read everything from a reader and split it into words.
+pub fn read_words<R: std::io::Read>(mut reader: R) -> std::io::Result<Vec<String>> {
+    let mut input = String::new();
+    reader.read_to_string(&mut input)?;
+    Ok(split_words(&input))
+}
+
+/// Uppercase a wrapped word.
+pub fn shout(word: &Wrapper<String>) -> String {
+    word.to_uppercase()
+}
+
 /// Join words with a custom separator and optional trailing terminator.
 pub fn join_words(words: &[impl AsRef<str>], sep: &str, term: Option<&str>) -> String {
     let mut out = words.iter().map(|w| w.as_ref()).collect::<Vec<_>>().join(sep);
@@ -56,6 +56,8 @@ interface Hit {
     link: string;
     docs?: string | null;
     signature: string;
+    trait?: string | null;
+    bindings?: Record<string, string>;
 }
 
 type CrateMetadata = {
@@ -347,7 +349,10 @@ async function presentSearch(host: string, initialScope: string, query: string,
             const mapped = safeHits.map((h: Hit) => ({
                 label: h.signature || h.name || '',
                 description: (h.path || []).join('::'),
-                detail: '',
+                detail: [
+                    h.trait ? `impl ${h.trait}` : '',
+                    ...Object.entries(h.bindings ?? {}).map(([name, ty]) => `?${name} = ${ty}`),
+                ].filter((d) => d).join(', '),
                 link: h.link,
                 alwaysShow: true as boolean,
             }));